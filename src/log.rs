@@ -0,0 +1,1409 @@
+//! Parsing of log lines into `Log` rows and the matching used by filter and search,
+//! independent of the window.
+
+use chrono::prelude::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+const FORMAT_SNIFF_LINES: usize = 20;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Level {
+    Unknown,
+    Debug,
+    Info,
+    Warning,
+    Error,
+    Panic,
+}
+
+impl Level {
+    pub fn from_string(level: &str) -> Level {
+        match level {
+            "DEBUG" => Level::Debug,
+            "INFO" => Level::Info,
+            "WARN" => Level::Warning,
+            "ERROR" => Level::Error,
+            "PANIC" => Level::Panic,
+            level => level.parse().map_or(Level::Unknown, Level::from_number),
+        }
+    }
+
+    /// Numeric severities, syslog's 0 (emergency) to 7 (debug) and the steps of ten bunyan
+    /// and pino use from 10 (trace) to 60 (fatal).
+    pub fn from_number(severity: i64) -> Level {
+        match severity {
+            0..=2 => Level::Panic,
+            3 => Level::Error,
+            4 => Level::Warning,
+            5 | 6 => Level::Info,
+            7 => Level::Debug,
+            10..=29 => Level::Debug,
+            30..=39 => Level::Info,
+            40..=49 => Level::Warning,
+            50..=59 => Level::Error,
+            60.. => Level::Panic,
+            _ => Level::Unknown,
+        }
+    }
+
+    /// Lenient variant of `from_string` for hand typed or lowercase level names.
+    pub fn from_name(name: &str) -> Level {
+        match name.to_uppercase().as_str() {
+            "WARNING" => Level::Warning,
+            "FATAL" => Level::Panic,
+            name => Level::from_string(name),
+        }
+    }
+
+    pub fn color(self) -> egui::Color32 {
+        match self {
+            Level::Debug => egui::Color32::from_rgb(10, 10, 240),
+            Level::Info => egui::Color32::from_rgb(10, 240, 10),
+            Level::Warning => egui::Color32::from_rgb(240, 240, 10),
+            Level::Error => egui::Color32::from_rgb(240, 60, 10),
+            Level::Panic => egui::Color32::from_rgb(240, 10, 10),
+            _ => egui::Color32::from_rgb(80, 80, 80),
+        }
+    }
+
+    pub fn to_string(self) -> &'static str {
+        match self {
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warning => "WARN",
+            Level::Error => "ERROR",
+            Level::Panic => "PANIC",
+            _ => "N/A",
+        }
+    }
+}
+
+/// A level name beyond the standard ones with its own label and color, ranked like `rank`
+/// when sorting and alerting.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct CustomLevel {
+    pub name: String,
+    pub label: String,
+    pub color: [u8; 3],
+    pub rank: Level,
+}
+
+impl Default for CustomLevel {
+    fn default() -> Self {
+        Self {
+            name: "".to_string(),
+            label: "".to_string(),
+            color: [160, 100, 240],
+            rank: Level::Info,
+        }
+    }
+}
+
+impl CustomLevel {
+    pub fn label(&self) -> &str {
+        if self.label.is_empty() { &self.name } else { &self.label }
+    }
+
+    pub fn color(&self) -> egui::Color32 {
+        let [r, g, b] = self.color;
+        egui::Color32::from_rgb(r, g, b)
+    }
+}
+
+#[derive(Clone)]
+pub struct Log {
+    pub time: Option<DateTime<FixedOffset>>,
+    pub level: Level,
+    pub message: String,
+    pub caller: String,
+    pub payload: String,
+    pub fields: Vec<String>,
+    /// The `stacktrace` field zap and similar loggers add to errors, kept out of the payload.
+    pub stacktrace: String,
+    pub raw: String,
+    pub time_raw: String,
+    pub level_raw: String,
+    /// Set when `level_raw` is one of the custom levels, `level` is then its rank.
+    pub custom_level: Option<std::sync::Arc<CustomLevel>>,
+    /// 1-based line in the file, set by the reader.
+    pub line: usize,
+    /// 0-based position in load order, unlike `line` it skips lines that gave no log.
+    pub index: usize,
+    /// Index into `Tab::sources` and the line in that file, for rows of a merged tab.
+    pub source: Option<(usize, usize)>,
+    /// The line did not parse, `message` holds it as is.
+    pub parse_error: bool,
+}
+
+impl Log {
+    pub fn from_line(line: &str, options: ParseOptions) -> Option<Log> {
+        let ParseOptions { format, text_pattern, sort_payload, skip_comments, custom_levels } = options;
+        let line = line.trim_start_matches('\u{feff}').trim_end_matches(['\r', '\n']);
+        if skip_comments && line.trim_start().starts_with('#') {
+            return None;
+        }
+        let parsed = match format {
+            LogFormat::Json => Log::from_json(line, sort_payload),
+            LogFormat::Logfmt => Log::from_logfmt(line, sort_payload),
+            LogFormat::Text => None,
+        };
+        let parsed = match (parsed, text_pattern) {
+            (None, Some(pattern)) => Log::from_text(line, pattern),
+            (log, _) => log,
+        };
+        let mut log = match parsed {
+            Some(log) => log,
+            None if line.trim().is_empty() => return None,
+            // Plain text without a pattern is all message, any other line failed to parse and
+            // is still shown in place.
+            None => Log {
+                time: None,
+                level: Level::Unknown,
+                message: line.to_string(),
+                payload: String::new(),
+                caller: String::new(),
+                fields: vec![],
+                stacktrace: String::new(),
+                raw: String::new(),
+                time_raw: String::new(),
+                level_raw: String::new(),
+                custom_level: None,
+                line: 0,
+                index: 0,
+                source: None,
+                parse_error: format != LogFormat::Text || text_pattern.is_some(),
+            },
+        };
+        if log.level == Level::Unknown {
+            let name = log.level_raw.trim();
+            if let Some(custom) = custom_levels.iter().find(|custom| !name.is_empty() && custom.name.trim().eq_ignore_ascii_case(name)) {
+                log.level = custom.rank;
+                log.custom_level = Some(custom.clone());
+            }
+        }
+        log.raw = line.to_string();
+        Some(log)
+    }
+
+    pub fn level_label(&self) -> &str {
+        self.custom_level.as_ref().map_or(self.level.to_string(), |custom| custom.label())
+    }
+
+    pub fn level_color(&self) -> egui::Color32 {
+        self.custom_level.as_ref().map_or(self.level.color(), |custom| custom.color())
+    }
+
+    /// Builds a log from the named groups of `pattern`, `ts`, `level`, `msg` and `caller`
+    /// fill the standard columns and every other group becomes an extra field.
+    fn from_text(line: &str, pattern: &regex::Regex) -> Option<Log> {
+        let captures = pattern.captures(line)?;
+        let group = |name: &str| captures.name(name).map(|m| m.as_str());
+        Some(Log {
+            time: group("ts").and_then(Log::time_from_string),
+            level: Level::from_string(&group("level").unwrap_or_default().to_uppercase()),
+            message: group("msg").unwrap_or(line).to_string(),
+            payload: String::new(),
+            caller: group("caller").unwrap_or_default().to_string(),
+            fields: text_fields(pattern).iter().map(|name| group(name).unwrap_or_default().to_string()).collect(),
+            stacktrace: group("stacktrace").unwrap_or_default().to_string(),
+            raw: String::new(),
+            time_raw: group("ts").unwrap_or_default().to_string(),
+            level_raw: group("level").unwrap_or_default().to_string(),
+            custom_level: None,
+            line: 0,
+            index: 0,
+            source: None,
+            parse_error: false,
+        })
+    }
+
+    /// Parses `key=value` pairs, well known keys fill the standard columns and the rest
+    /// becomes the payload.
+    fn from_logfmt(line: &str, sort_payload: bool) -> Option<Log> {
+        let pairs = logfmt_pairs(line);
+        if !pairs.iter().any(|(_, value)| value.is_some()) {
+            return None;
+        }
+        let mut log = Log {
+            time: None,
+            level: Level::Unknown,
+            message: String::new(),
+            payload: String::new(),
+            caller: String::new(),
+            fields: vec![],
+            stacktrace: String::new(),
+            raw: String::new(),
+            time_raw: String::new(),
+            level_raw: String::new(),
+            custom_level: None,
+            line: 0,
+            index: 0,
+            source: None,
+            parse_error: false,
+        };
+        let mut payload = serde_json::Map::new();
+        for (key, value) in pairs {
+            let value = value.unwrap_or_default();
+            match key.as_str() {
+                "ts" | "time" | "timestamp" => {
+                    log.time = Log::time_from_string(&value);
+                    log.time_raw = value;
+                }
+                "level" | "lvl" => {
+                    log.level = Level::from_name(&value);
+                    log.level_raw = value;
+                }
+                "msg" | "message" => log.message = value,
+                "caller" => log.caller = value,
+                "stacktrace" => log.stacktrace = value,
+                _ => {
+                    payload.insert(key, serde_json::Value::String(value));
+                }
+            }
+        }
+        log.payload = payload_to_string(payload, sort_payload);
+        Some(log)
+    }
+
+    fn from_json(json_str: &str, sort_payload: bool) -> Option<Log> {
+        let value = serde_json::from_str::<serde_json::Value>(json_str).ok()?;
+        let mut json_line = serde_json::from_value::<JsonLine>(value).ok()?;
+        // Some loggers leave out the level and write a numeric `severity` instead.
+        let level = match json_line.level.take() {
+            serde_json::Value::Null => json_line.payload.remove("severity")?,
+            level => level,
+        };
+        let (level, level_raw) = match level {
+            serde_json::Value::String(level) => (Level::from_string(&level), level),
+            serde_json::Value::Number(severity) => (severity.as_i64().map_or(Level::Unknown, Level::from_number), severity.to_string()),
+            _ => return None,
+        };
+        let payload = payload_to_string(json_line.payload, sort_payload);
+
+        Some(Log {
+            time: Log::time_from_value(&json_line.ts),
+            level,
+            message: json_line.msg,
+            payload: payload.to_string(),
+            caller: json_line.caller,
+            fields: vec![],
+            stacktrace: match json_line.stacktrace {
+                serde_json::Value::String(stacktrace) => stacktrace,
+                serde_json::Value::Null => String::new(),
+                stacktrace => serde_json::to_string_pretty(&stacktrace).unwrap_or_default(),
+            },
+            raw: String::new(),
+            time_raw: match json_line.ts {
+                serde_json::Value::String(ts) => ts,
+                serde_json::Value::Null => String::new(),
+                ts => ts.to_string(),
+            },
+            level_raw,
+            custom_level: None,
+            line: 0,
+            index: 0,
+            source: None,
+            parse_error: false,
+        })
+    }
+
+    pub fn heap_size(&self) -> usize {
+        let fields: usize = self.fields.iter().map(String::capacity).sum();
+        std::mem::size_of::<Log>() + self.message.capacity() + self.caller.capacity() + self.payload.capacity() + self.raw.capacity() + self.time_raw.capacity() + self.level_raw.capacity() + self.stacktrace.capacity() + fields
+    }
+
+    fn time_from_value(ts: &serde_json::Value) -> Option<DateTime<FixedOffset>> {
+        match ts {
+            serde_json::Value::String(time_string) => Log::time_from_string(time_string),
+            serde_json::Value::Number(epoch) => Log::time_from_epoch(&epoch.to_string()),
+            _ => None,
+        }
+    }
+
+    fn time_from_string(time_string: &str) -> Option<DateTime<FixedOffset>> {
+        if let Some(ts) = Log::time_from_epoch(time_string) {
+            return Some(ts);
+        }
+        if let Ok(ts) = DateTime::parse_from_rfc3339(time_string) {
+            return Some(ts);
+        }
+        // `%.f` takes any number of fractional digits, up to nanoseconds.
+        for format in ["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%:z"] {
+            if let Ok(ts) = DateTime::parse_from_str(time_string, format) {
+                return Some(ts);
+            }
+        }
+        // Without an offset the time is taken as local.
+        for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+            if let Ok(ts) = chrono::NaiveDateTime::parse_from_str(time_string, format) {
+                return Local.from_local_datetime(&ts).earliest().map(|ts| ts.fixed_offset());
+            }
+        }
+        None
+    }
+
+    /// Parses a numeric epoch, guessing seconds, millis, micros or nanos from its magnitude.
+    fn time_from_epoch(epoch: &str) -> Option<DateTime<FixedOffset>> {
+        let (int, fraction) = epoch.split_once('.').unwrap_or((epoch, ""));
+        if !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let int: i64 = int.parse().ok()?;
+        let (secs, nanos) = match int.unsigned_abs() {
+            n if n < 100_000_000_000 => {
+                let digits = format!("{:0<9}", &fraction[..fraction.len().min(9)]);
+                (int, digits.parse().ok()?)
+            }
+            n if n < 100_000_000_000_000 => (int.div_euclid(1_000), int.rem_euclid(1_000) * 1_000_000),
+            n if n < 100_000_000_000_000_000 => (int.div_euclid(1_000_000), int.rem_euclid(1_000_000) * 1_000),
+            _ => (int.div_euclid(1_000_000_000), int.rem_euclid(1_000_000_000)),
+        };
+        Local.timestamp_opt(secs, nanos as u32).single().map(|ts| ts.fixed_offset())
+    }
+
+    pub fn time_to_string(&self, utc: bool, precision: TimePrecision) -> String {
+        format_time(self.time, utc, precision)
+    }
+
+    /// With `verbatim` the timestamp text of the line, when it had one, otherwise the
+    /// parsed time formatted.
+    pub fn time_display(&self, verbatim: bool, utc: bool, precision: TimePrecision) -> String {
+        match verbatim && !self.time_raw.is_empty() {
+            true => self.time_raw.clone(),
+            false => self.time_to_string(utc, precision),
+        }
+    }
+}
+
+pub fn format_time(time: Option<DateTime<FixedOffset>>, utc: bool, precision: TimePrecision) -> String {
+    let format = precision.seconds_format();
+    match time {
+        Some(time) if utc => time.with_timezone(&Utc).to_rfc3339_opts(format, false),
+        Some(time) => time.with_timezone(&Local).to_rfc3339_opts(format, false),
+        None => "—".to_string(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonLine {
+    #[serde(default)]
+    level: serde_json::Value,
+    #[serde(default)]
+    ts: serde_json::Value,
+    msg: String,
+    #[serde(default)]
+    caller: String,
+    #[serde(default)]
+    stacktrace: serde_json::Value,
+    #[serde(flatten)]
+    payload: serde_json::Map<String, serde_json::Value>,
+}
+
+/// How the Time column shows timestamps, search and export use the same text.
+#[derive(PartialEq, Clone, Copy)]
+pub struct TimeFormat {
+    /// The timestamp text of the line instead of the parsed time, when it had one.
+    pub verbatim: bool,
+    pub utc: bool,
+    pub precision: TimePrecision,
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        TimeFormat { verbatim: false, utc: false, precision: TimePrecision::Auto }
+    }
+}
+
+impl TimeFormat {
+    pub fn display(self, log: &Log) -> String {
+        log.time_display(self.verbatim, self.utc, self.precision)
+    }
+}
+
+/// Fractional digits shown for timestamps, parsing always keeps the full precision.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum TimePrecision {
+    Auto,
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimePrecision {
+    pub fn name(self) -> &'static str {
+        match self {
+            TimePrecision::Auto => "Auto",
+            TimePrecision::Seconds => "Seconds",
+            TimePrecision::Millis => "Milliseconds",
+            TimePrecision::Micros => "Microseconds",
+            TimePrecision::Nanos => "Nanoseconds",
+        }
+    }
+
+    fn seconds_format(self) -> chrono::SecondsFormat {
+        match self {
+            TimePrecision::Auto => chrono::SecondsFormat::AutoSi,
+            TimePrecision::Seconds => chrono::SecondsFormat::Secs,
+            TimePrecision::Millis => chrono::SecondsFormat::Millis,
+            TimePrecision::Micros => chrono::SecondsFormat::Micros,
+            TimePrecision::Nanos => chrono::SecondsFormat::Nanos,
+        }
+    }
+}
+
+/// How lines of a tab are parsed into logs.
+#[derive(Clone, Copy)]
+pub struct ParseOptions<'a> {
+    pub format: LogFormat,
+    pub text_pattern: Option<&'a regex::Regex>,
+    pub sort_payload: bool,
+    /// Lines starting with `#` are dropped like blank lines.
+    pub skip_comments: bool,
+    pub custom_levels: &'a [std::sync::Arc<CustomLevel>],
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum LogFormat {
+    Json,
+    Logfmt,
+    Text,
+}
+
+impl LogFormat {
+    /// Guesses the format from the first non empty lines, JSON starts with `{` and logfmt
+    /// is mostly `key=value` pairs, anything else is left to the text pattern.
+    pub fn detect(lines: impl Iterator<Item = String>) -> LogFormat {
+        let lines: Vec<String> = lines
+            .map(|line| line.trim_start_matches('\u{feff}').to_string())
+            // Comments from shell wrappers say nothing about the format.
+            .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+            .take(FORMAT_SNIFF_LINES)
+            .collect();
+        let json = lines.iter().filter(|line| line.trim_start().starts_with('{')).count();
+        let logfmt = lines.iter().filter(|line| is_logfmt(line)).count();
+        if lines.is_empty() || json * 2 >= lines.len() {
+            LogFormat::Json
+        } else if logfmt * 2 >= lines.len() {
+            LogFormat::Logfmt
+        } else {
+            LogFormat::Text
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogFormat::Json => "JSON",
+            LogFormat::Logfmt => "logfmt",
+            LogFormat::Text => "Text",
+        }
+    }
+}
+
+/// Serializes the payload keys in file order, or with `sort` sorted by key at every level.
+fn payload_to_string(payload: serde_json::Map<String, serde_json::Value>, sort: bool) -> String {
+    match payload.len() {
+        0 => String::new(),
+        // A single scalar key has nothing to reorder.
+        1 if !sort || !payload.values().any(|value| value.is_object() || value.is_array()) => serde_json::Value::Object(payload).to_string(),
+        _ if sort => sort_keys(serde_json::Value::Object(payload)).to_string(),
+        _ => serde_json::Value::Object(payload).to_string(),
+    }
+}
+
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<(String, serde_json::Value)> = map.into_iter().map(|(key, value)| (key, sort_keys(value))).collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            serde_json::Value::Object(entries.into_iter().collect())
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(sort_keys).collect()),
+        value => value,
+    }
+}
+
+/// Whether `line` looks like logfmt, at least two values and at most half of the words bare.
+pub fn is_logfmt(line: &str) -> bool {
+    let pairs = logfmt_pairs(line);
+    let values = pairs.iter().filter(|(_, value)| value.is_some()).count();
+    values >= 2 && values * 2 >= pairs.len()
+}
+
+/// The value at the key `path` in a JSON payload, strings without their quotes.
+pub fn payload_value(payload: &str, path: &[String]) -> Option<String> {
+    let payload = serde_json::from_str::<serde_json::Value>(payload).ok()?;
+    match path.iter().try_fold(&payload, |value, key| value.get(key))? {
+        serde_json::Value::String(text) => Some(text.clone()),
+        value => Some(value.to_string()),
+    }
+}
+
+/// Splits a logfmt line into keys and optional values, values may be double quoted.
+pub fn logfmt_pairs(line: &str) -> Vec<(String, Option<String>)> {
+    let mut pairs = vec![];
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && c != '=') {
+            key.push(c);
+        }
+        if chars.next_if_eq(&'=').is_none() {
+            if key.is_empty() {
+                return pairs;
+            }
+            pairs.push((key, None));
+            continue;
+        }
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => value.extend(chars.next()),
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+        pairs.push((key, Some(value)));
+    }
+}
+
+const TEXT_PATTERN_FIELDS: [&str; 4] = ["ts", "level", "msg", "caller"];
+
+/// Names of the capture groups that become extra columns.
+pub fn text_fields(pattern: &regex::Regex) -> Vec<&str> {
+    pattern.capture_names().flatten().filter(|name| !TEXT_PATTERN_FIELDS.contains(name)).collect()
+}
+
+/// Level match shared by search and the search terms promoted into the filter, rows with a
+/// custom level match by its name.
+pub fn level_match(row: &Log, levels: [bool; 5], custom_levels: &[String]) -> bool {
+    if let Some(custom) = &row.custom_level {
+        return custom_levels.contains(&custom.name);
+    }
+    match row.level {
+        Level::Debug => levels[0],
+        Level::Info => levels[1],
+        Level::Warning => levels[2],
+        Level::Error => levels[3],
+        Level::Panic => levels[4],
+        Level::Unknown => true,
+    }
+}
+
+/// Substring match shared by search and the search terms promoted into the filter.
+pub fn terms_match(row: &Log, message: &str, payload: &str, caller: &str, accents: bool, whole_word: bool) -> bool {
+    text_match(&row.message, message, accents, whole_word)
+        && text_match(&row.payload, payload, accents, whole_word)
+        && text_match(&row.caller, caller, accents, whole_word)
+}
+
+/// Case insensitive substring match, with `whole_word` the match must not touch a letter,
+/// digit or underscore on either side, like `\bneedle\b`.
+pub fn text_match(text: &str, needle: &str, accents: bool, whole_word: bool) -> bool {
+    if needle.is_empty() {
+        return true;
+    }
+    let (text, needle) = (fold(text, accents), fold(needle, accents));
+    if !whole_word {
+        return text.contains(&needle);
+    }
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(&needle).any(|(start, found)| {
+        let before = text[..start].chars().next_back().map_or(true, |c| !is_word(c));
+        let after = text[start + found.len()..].chars().next().map_or(true, |c| !is_word(c));
+        before && after
+    })
+}
+
+/// Case folds `text` for matching. This is `to_lowercase` plus the full folds it leaves
+/// out, like `ß` to `ss` and final sigma, with `accents` diacritics are dropped as well
+/// so `café` matches `cafe`.
+pub fn fold(text: &str, accents: bool) -> String {
+    if text.is_ascii() {
+        return text.to_ascii_lowercase();
+    }
+    let folded = text.to_lowercase().chars().fold(String::with_capacity(text.len()), |mut folded, c| {
+        match c {
+            'ß' => folded.push_str("ss"),
+            'ς' => folded.push('σ'),
+            'ſ' => folded.push('s'),
+            'ﬀ' => folded.push_str("ff"),
+            'ﬁ' => folded.push_str("fi"),
+            'ﬂ' => folded.push_str("fl"),
+            c => folded.push(c),
+        }
+        folded
+    });
+    if !accents {
+        return folded;
+    }
+    use unicode_normalization::UnicodeNormalization;
+    folded.nfd().filter(|&c| !unicode_normalization::char::is_combining_mark(c)).collect()
+}
+
+/// A parsed filter query such as `level:error AND caller:db* NOT payload.retries<3`.
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Term(QueryTerm),
+}
+
+pub struct QueryTerm {
+    field: QueryField,
+    op: QueryOp,
+    value: String,
+    pattern: QueryPattern,
+}
+
+enum QueryField {
+    Any,
+    Level,
+    Message,
+    Caller,
+    Payload,
+    PayloadKey(Vec<String>),
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum QueryOp {
+    Match,
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+enum QueryPattern {
+    Contains(String),
+    Regex(regex::Regex),
+}
+
+#[derive(PartialEq)]
+enum QueryToken {
+    Open,
+    Close,
+    Op(QueryOp),
+    Word(String),
+    Quoted(String),
+    Regex(String),
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Query, String> {
+        let tokens = Query::tokenize(query)?;
+        let mut pos = 0;
+        let parsed = Query::parse_or(&tokens, &mut pos)?;
+        match tokens.get(pos) {
+            None => Ok(parsed),
+            Some(_) => Err("unexpected ')'".to_string()),
+        }
+    }
+
+    fn tokenize(query: &str) -> Result<Vec<QueryToken>, String> {
+        let chars: Vec<char> = query.chars().collect();
+        let mut tokens = vec![];
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            i += 1;
+            match c {
+                c if c.is_whitespace() => {}
+                '(' => tokens.push(QueryToken::Open),
+                ')' => tokens.push(QueryToken::Close),
+                ':' => tokens.push(QueryToken::Op(QueryOp::Match)),
+                '=' => tokens.push(QueryToken::Op(QueryOp::Eq)),
+                '<' | '>' => {
+                    let or_equal = chars.get(i) == Some(&'=');
+                    if or_equal {
+                        i += 1;
+                    }
+                    tokens.push(QueryToken::Op(match (c, or_equal) {
+                        ('<', false) => QueryOp::Lt,
+                        ('<', true) => QueryOp::Le,
+                        (_, false) => QueryOp::Gt,
+                        (_, true) => QueryOp::Ge,
+                    }));
+                }
+                '"' => {
+                    let mut phrase = String::new();
+                    loop {
+                        match chars.get(i) {
+                            None => return Err("unterminated quote".to_string()),
+                            Some('"') => break,
+                            Some('\\') if chars.get(i + 1) == Some(&'"') => {
+                                phrase.push('"');
+                                i += 1;
+                            }
+                            Some(&c) => phrase.push(c),
+                        }
+                        i += 1;
+                    }
+                    i += 1;
+                    tokens.push(QueryToken::Quoted(phrase));
+                }
+                '/' if matches!(tokens.last(), Some(QueryToken::Op(_))) => {
+                    let Some(end) = chars[i..].iter().position(|&c| c == '/') else {
+                        return Err("unterminated regex".to_string());
+                    };
+                    tokens.push(QueryToken::Regex(chars[i..i + end].iter().collect()));
+                    i += end + 1;
+                }
+                _ => {
+                    let mut word = c.to_string();
+                    while let Some(&c) = chars.get(i) {
+                        if c.is_whitespace() || "():=<>\"".contains(c) {
+                            break;
+                        }
+                        word.push(c);
+                        i += 1;
+                    }
+                    tokens.push(QueryToken::Word(word));
+                }
+            }
+        }
+        Ok(tokens)
+    }
+
+    fn is_keyword(token: Option<&QueryToken>, keyword: &str) -> bool {
+        matches!(token, Some(QueryToken::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(tokens: &[QueryToken], pos: &mut usize) -> Result<Query, String> {
+        let mut left = Query::parse_and(tokens, pos)?;
+        while Query::is_keyword(tokens.get(*pos), "OR") {
+            *pos += 1;
+            let right = Query::parse_and(tokens, pos)?;
+            left = Query::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(tokens: &[QueryToken], pos: &mut usize) -> Result<Query, String> {
+        let mut left = Query::parse_unary(tokens, pos)?;
+        loop {
+            if Query::is_keyword(tokens.get(*pos), "AND") {
+                *pos += 1;
+            } else if tokens.get(*pos).is_none()
+                || tokens.get(*pos) == Some(&QueryToken::Close)
+                || Query::is_keyword(tokens.get(*pos), "OR") {
+                return Ok(left);
+            }
+            let right = Query::parse_unary(tokens, pos)?;
+            left = Query::And(Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_unary(tokens: &[QueryToken], pos: &mut usize) -> Result<Query, String> {
+        if Query::is_keyword(tokens.get(*pos), "NOT") {
+            *pos += 1;
+            return Ok(Query::Not(Box::new(Query::parse_unary(tokens, pos)?)));
+        }
+        Query::parse_primary(tokens, pos)
+    }
+
+    fn parse_primary(tokens: &[QueryToken], pos: &mut usize) -> Result<Query, String> {
+        let token = tokens.get(*pos).ok_or("unexpected end of query")?;
+        *pos += 1;
+        match token {
+            QueryToken::Open => {
+                let inner = Query::parse_or(tokens, pos)?;
+                if tokens.get(*pos) != Some(&QueryToken::Close) {
+                    return Err("missing ')'".to_string());
+                }
+                *pos += 1;
+                Ok(inner)
+            }
+            QueryToken::Word(word) => {
+                if let Some(QueryToken::Op(op)) = tokens.get(*pos) {
+                    *pos += 1;
+                    let field = QueryField::from_name(word)?;
+                    let value = tokens.get(*pos).ok_or(format!("missing value for '{}'", word))?;
+                    *pos += 1;
+                    return Ok(Query::Term(QueryTerm::new(field, *op, value)?));
+                }
+                Ok(Query::Term(QueryTerm::new(QueryField::Any, QueryOp::Match, token)?))
+            }
+            QueryToken::Quoted(_) | QueryToken::Regex(_) => Ok(Query::Term(QueryTerm::new(QueryField::Any, QueryOp::Match, token)?)),
+            QueryToken::Close => Err("unexpected ')'".to_string()),
+            QueryToken::Op(_) => Err("unexpected operator".to_string()),
+        }
+    }
+
+    pub fn matches(&self, log: &Log, accents: bool) -> bool {
+        match self {
+            Query::And(left, right) => left.matches(log, accents) && right.matches(log, accents),
+            Query::Or(left, right) => left.matches(log, accents) || right.matches(log, accents),
+            Query::Not(inner) => !inner.matches(log, accents),
+            Query::Term(term) => term.matches(log, accents),
+        }
+    }
+}
+
+impl QueryField {
+    fn from_name(name: &str) -> Result<QueryField, String> {
+        match name.to_lowercase().as_str() {
+            "level" | "lvl" => Ok(QueryField::Level),
+            "msg" | "message" => Ok(QueryField::Message),
+            "caller" => Ok(QueryField::Caller),
+            "payload" => Ok(QueryField::Payload),
+            _ => match name.strip_prefix("payload.") {
+                Some(path) if !path.is_empty() => Ok(QueryField::PayloadKey(path.split('.').map(str::to_string).collect())),
+                _ => Err(format!("unknown field '{}'", name)),
+            },
+        }
+    }
+}
+
+impl QueryTerm {
+    fn new(field: QueryField, op: QueryOp, token: &QueryToken) -> Result<QueryTerm, String> {
+        let (value, pattern) = match token {
+            QueryToken::Word(word) if word.contains(['*', '?']) => {
+                let glob = regex::escape(word).replace(r"\*", ".*").replace(r"\?", ".");
+                (word.clone(), QueryPattern::regex(&format!("^{}$", glob))?)
+            }
+            QueryToken::Word(word) | QueryToken::Quoted(word) => (word.clone(), QueryPattern::Contains(fold(word, false))),
+            QueryToken::Regex(pattern) => (pattern.clone(), QueryPattern::regex(pattern)?),
+            _ => return Err("expected a value".to_string()),
+        };
+        Ok(QueryTerm { field, op, value, pattern })
+    }
+
+    fn matches(&self, log: &Log, accents: bool) -> bool {
+        match &self.field {
+            QueryField::Any => [&log.message, &log.payload, &log.caller].iter().any(|text| self.pattern.matches(text, accents)),
+            QueryField::Level => {
+                self.compare(Some(log.level.cmp(&Level::from_name(&self.value))))
+            }
+            QueryField::Message => self.matches_text(&log.message, accents),
+            QueryField::Caller => self.matches_text(&log.caller, accents),
+            QueryField::Payload => self.matches_text(&log.payload, accents),
+            QueryField::PayloadKey(path) => payload_value(&log.payload, path).is_some_and(|value| self.matches_text(&value, accents)),
+        }
+    }
+
+    fn matches_text(&self, text: &str, accents: bool) -> bool {
+        match self.op {
+            QueryOp::Match => self.pattern.matches(text, accents),
+            QueryOp::Eq => fold(text, accents) == fold(&self.value, accents),
+            _ => match (text.parse::<f64>(), self.value.parse::<f64>()) {
+                (Ok(left), Ok(right)) => self.compare(left.partial_cmp(&right)),
+                _ => false,
+            },
+        }
+    }
+
+    fn compare(&self, ordering: Option<std::cmp::Ordering>) -> bool {
+        use std::cmp::Ordering;
+        matches!(
+            (self.op, ordering),
+            (QueryOp::Match | QueryOp::Eq, Some(Ordering::Equal))
+                | (QueryOp::Lt, Some(Ordering::Less))
+                | (QueryOp::Le, Some(Ordering::Less | Ordering::Equal))
+                | (QueryOp::Gt, Some(Ordering::Greater))
+                | (QueryOp::Ge, Some(Ordering::Greater | Ordering::Equal))
+        )
+    }
+}
+
+impl QueryPattern {
+    fn regex(pattern: &str) -> Result<QueryPattern, String> {
+        regex::RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(QueryPattern::Regex)
+            .map_err(|err| err.to_string())
+    }
+
+    fn matches(&self, text: &str, accents: bool) -> bool {
+        match self {
+            QueryPattern::Contains(needle) if accents => fold(text, true).contains(&fold(needle, true)),
+            QueryPattern::Contains(needle) => fold(text, false).contains(needle),
+            QueryPattern::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+
+/// A JSONPath payload filter such as `$.request.headers.host == "api"`. Without a
+/// comparison it matches payloads where the path selects anything.
+pub struct JsonPath {
+    steps: Vec<JsonPathStep>,
+    compare: Option<(JsonPathOp, serde_json::Value)>,
+}
+
+enum JsonPathStep {
+    Key(String),
+    Index(usize),
+    Wildcard,
+    /// `..key`, the key at any depth.
+    Descend(String),
+}
+
+#[derive(Clone, Copy)]
+enum JsonPathOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl JsonPath {
+    pub fn parse(expression: &str) -> Result<JsonPath, String> {
+        let Some(mut rest) = expression.trim().strip_prefix('$') else {
+            return Err("a path starts with $".to_string());
+        };
+        let name_end = |text: &str| text.find(|c: char| c.is_whitespace() || ".[=!<>".contains(c)).unwrap_or(text.len());
+        let mut steps = vec![];
+        loop {
+            if let Some(after) = rest.strip_prefix("..") {
+                let end = name_end(after);
+                if end == 0 {
+                    return Err("expected a key after ..".to_string());
+                }
+                steps.push(JsonPathStep::Descend(after[..end].to_string()));
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let end = name_end(after);
+                steps.push(match &after[..end] {
+                    "" => return Err("expected a key after .".to_string()),
+                    "*" => JsonPathStep::Wildcard,
+                    key => JsonPathStep::Key(key.to_string()),
+                });
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let Some(end) = after.find(']') else {
+                    return Err("missing ]".to_string());
+                };
+                let inside = after[..end].trim();
+                steps.push(if inside == "*" {
+                    JsonPathStep::Wildcard
+                } else if let Ok(index) = inside.parse() {
+                    JsonPathStep::Index(index)
+                } else if inside.len() >= 2 && (inside.starts_with('\'') && inside.ends_with('\'') || inside.starts_with('"') && inside.ends_with('"')) {
+                    JsonPathStep::Key(inside[1..inside.len() - 1].to_string())
+                } else {
+                    return Err(format!("unexpected [{}]", inside));
+                });
+                rest = &after[end + 1..];
+            } else {
+                break;
+            }
+        }
+
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Ok(JsonPath { steps, compare: None });
+        }
+        let ops = [("==", JsonPathOp::Eq), ("!=", JsonPathOp::Ne), ("<=", JsonPathOp::Le), (">=", JsonPathOp::Ge), ("<", JsonPathOp::Lt), (">", JsonPathOp::Gt)];
+        let Some((op, value)) = ops.iter().find_map(|(token, op)| rest.strip_prefix(token).map(|value| (*op, value.trim()))) else {
+            return Err(format!("expected a comparison at {}", rest));
+        };
+        if value.is_empty() {
+            return Err("expected a value".to_string());
+        }
+        // Bare words are taken as strings so `== api` works like `== "api"`.
+        let value = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+        Ok(JsonPath { steps, compare: Some((op, value)) })
+    }
+
+    pub fn matches(&self, payload: &str) -> bool {
+        let Ok(payload) = serde_json::from_str::<serde_json::Value>(payload) else {
+            return false;
+        };
+        let mut selected = vec![&payload];
+        for step in &self.steps {
+            selected = selected.into_iter().flat_map(|value| step.select(value)).collect();
+        }
+        match &self.compare {
+            None => !selected.is_empty(),
+            Some((op, expected)) => selected.iter().any(|value| op.holds(value, expected)),
+        }
+    }
+}
+
+impl JsonPathStep {
+    fn select<'a>(&self, value: &'a serde_json::Value) -> Vec<&'a serde_json::Value> {
+        match (self, value) {
+            (JsonPathStep::Key(key), serde_json::Value::Object(map)) => map.get(key).into_iter().collect(),
+            (JsonPathStep::Index(index), serde_json::Value::Array(items)) => items.get(*index).into_iter().collect(),
+            (JsonPathStep::Wildcard, serde_json::Value::Object(map)) => map.values().collect(),
+            (JsonPathStep::Wildcard, serde_json::Value::Array(items)) => items.iter().collect(),
+            (JsonPathStep::Descend(key), _) => {
+                let mut found = vec![];
+                let mut pending = vec![value];
+                while let Some(value) = pending.pop() {
+                    match value {
+                        serde_json::Value::Object(map) => {
+                            found.extend(map.get(key));
+                            pending.extend(map.values());
+                        }
+                        serde_json::Value::Array(items) => pending.extend(items),
+                        _ => {}
+                    }
+                }
+                found
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl JsonPathOp {
+    /// Numbers compare by value and strings by text, anything else only by equality.
+    fn holds(self, value: &serde_json::Value, expected: &serde_json::Value) -> bool {
+        use std::cmp::Ordering;
+        let ordering = match (value, expected) {
+            (serde_json::Value::Number(left), serde_json::Value::Number(right)) => left.as_f64().zip(right.as_f64()).and_then(|(left, right)| left.partial_cmp(&right)),
+            (serde_json::Value::String(left), serde_json::Value::String(right)) => Some(left.cmp(right)),
+            _ => (value == expected).then_some(Ordering::Equal),
+        };
+        match self {
+            JsonPathOp::Eq => ordering == Some(Ordering::Equal),
+            JsonPathOp::Ne => ordering != Some(Ordering::Equal),
+            JsonPathOp::Lt => ordering == Some(Ordering::Less),
+            JsonPathOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            JsonPathOp::Gt => ordering == Some(Ordering::Greater),
+            JsonPathOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(format: LogFormat, text_pattern: Option<&regex::Regex>) -> ParseOptions<'_> {
+        ParseOptions { format, text_pattern, sort_payload: false, skip_comments: false, custom_levels: &[] }
+    }
+
+    fn utc(time: &str) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(time).ok()
+    }
+
+    #[test]
+    fn from_line_json() {
+        let line = r#"{"level":"ERROR","ts":"2024-03-01T10:32:05Z","msg":"db down","caller":"db.go:12","retries":3}"#;
+        let log = Log::from_line(line, options(LogFormat::Json, None)).unwrap();
+        assert_eq!(log.level, Level::Error);
+        assert_eq!(log.time, utc("2024-03-01T10:32:05Z"));
+        assert_eq!(log.message, "db down");
+        assert_eq!(log.caller, "db.go:12");
+        assert_eq!(log.payload, r#"{"retries":3}"#);
+        assert_eq!(log.raw, line);
+        assert!(!log.parse_error);
+    }
+
+    #[test]
+    fn from_line_logfmt() {
+        let line = r#"ts=2024-03-01T10:32:05Z level=warn msg="slow query" caller=db.go:40 took=2s"#;
+        let log = Log::from_line(line, options(LogFormat::Logfmt, None)).unwrap();
+        assert_eq!(log.level, Level::Warning);
+        assert_eq!(log.time, utc("2024-03-01T10:32:05Z"));
+        assert_eq!(log.message, "slow query");
+        assert_eq!(log.caller, "db.go:40");
+        assert_eq!(log.payload, r#"{"took":"2s"}"#);
+    }
+
+    #[test]
+    fn from_line_text_pattern() {
+        let pattern = regex::Regex::new(r"(?P<ts>\S+) (?P<level>\w+) \[(?P<thread>\w+)\] (?P<msg>.*)").unwrap();
+        let log = Log::from_line("2024-03-01T10:32:05Z info [main] started", options(LogFormat::Text, Some(&pattern))).unwrap();
+        assert_eq!(log.level, Level::Info);
+        assert_eq!(log.time, utc("2024-03-01T10:32:05Z"));
+        assert_eq!(log.message, "started");
+        assert_eq!(log.fields, vec!["main".to_string()]);
+        assert!(!log.parse_error);
+    }
+
+    #[test]
+    fn from_line_malformed() {
+        let log = Log::from_line("not json at all", options(LogFormat::Json, None)).unwrap();
+        assert!(log.parse_error);
+        assert_eq!(log.level, Level::Unknown);
+        assert_eq!(log.message, "not json at all");
+        // Plain text without a pattern is not an error.
+        assert!(!Log::from_line("just text", options(LogFormat::Text, None)).unwrap().parse_error);
+        assert!(Log::from_line("   ", options(LogFormat::Json, None)).is_none());
+        let skip = ParseOptions { skip_comments: true, ..options(LogFormat::Json, None) };
+        assert!(Log::from_line("# comment", skip).is_none());
+    }
+
+    #[test]
+    fn time_from_string_formats() {
+        assert_eq!(Log::time_from_string("2024-03-01T10:32:05+02:00"), utc("2024-03-01T10:32:05+02:00"));
+        assert_eq!(Log::time_from_string("2024-03-01 10:32:05+0200"), utc("2024-03-01T10:32:05+02:00"));
+        assert_eq!(Log::time_from_string("2024-03-01 10:32:05+02:00"), utc("2024-03-01T10:32:05+02:00"));
+        assert_eq!(Log::time_from_string("1709289125"), utc("2024-03-01T10:32:05Z"));
+        assert!(Log::time_from_string("2024-03-01T10:32:05").is_some());
+        assert!(Log::time_from_string("yesterday").is_none());
+    }
+
+    #[test]
+    fn time_from_string_fractions() {
+        let time = |text: &str| Log::time_from_string(text).unwrap();
+        assert_eq!(time("2024-03-01T10:32:05.123Z").timestamp_subsec_nanos(), 123_000_000);
+        assert_eq!(time("2024-03-01T10:32:05.123456Z").timestamp_subsec_nanos(), 123_456_000);
+        assert_eq!(time("2024-03-01T10:32:05.123456789Z").timestamp_subsec_nanos(), 123_456_789);
+        assert_eq!(time("2024-03-01T10:32:05.123456789+02:00").timestamp_subsec_nanos(), 123_456_789);
+        assert_eq!(time("2024-03-01T10:32:05Z"), utc("2024-03-01T10:32:05+00:00").unwrap());
+        assert_eq!(time("2024-03-01T10:32:05Z").offset().local_minus_utc(), 0);
+    }
+
+    #[test]
+    fn time_from_epoch_fraction() {
+        assert_eq!(Log::time_from_epoch("1709289125.5"), utc("2024-03-01T10:32:05.5Z"));
+        assert!(Log::time_from_epoch("1709289125.5e3").is_none());
+        assert!(Log::time_from_epoch("abc").is_none());
+    }
+
+    #[test]
+    fn time_from_epoch_units() {
+        assert_eq!(Log::time_from_epoch("1709289125"), utc("2024-03-01T10:32:05Z"));
+        assert_eq!(Log::time_from_epoch("1709289125123"), utc("2024-03-01T10:32:05.123Z"));
+        assert_eq!(Log::time_from_epoch("1709289125123456"), utc("2024-03-01T10:32:05.123456Z"));
+        assert_eq!(Log::time_from_epoch("1709289125123456789"), utc("2024-03-01T10:32:05.123456789Z"));
+        assert_eq!(Log::time_from_value(&serde_json::json!(1709289125123u64)), utc("2024-03-01T10:32:05.123Z"));
+    }
+
+    #[test]
+    fn level_names() {
+        assert_eq!(Level::from_string("WARN"), Level::Warning);
+        assert_eq!(Level::from_string("warn"), Level::Unknown);
+        assert_eq!(Level::from_string("TRACE"), Level::Unknown);
+        assert_eq!(Level::from_name("warning"), Level::Warning);
+        assert_eq!(Level::from_name("Fatal"), Level::Panic);
+        assert_eq!(Level::from_name("error"), Level::Error);
+        assert_eq!(Level::from_number(3), Level::Error);
+        assert_eq!(Level::from_number(7), Level::Debug);
+    }
+
+    #[test]
+    fn level_from_number() {
+        let pino = [(10, Level::Debug), (20, Level::Debug), (30, Level::Info), (40, Level::Warning), (50, Level::Error), (60, Level::Panic)];
+        for (severity, level) in pino {
+            assert_eq!(Level::from_number(severity), level, "{severity}");
+        }
+        assert_eq!(Level::from_number(0), Level::Panic);
+        assert_eq!(Level::from_number(6), Level::Info);
+        assert_eq!(Level::from_string("30"), Level::Info);
+        assert_eq!(Level::from_string("50"), Level::Error);
+        for severity in [-1, 8, 9] {
+            assert_eq!(Level::from_number(severity), Level::Unknown, "{severity}");
+        }
+        assert_eq!(Level::from_string("3.5"), Level::Unknown);
+    }
+
+    #[test]
+    fn from_line_numeric_level() {
+        let log = Log::from_line(r#"{"level":40,"msg":"slow"}"#, options(LogFormat::Json, None)).unwrap();
+        assert_eq!((log.level, log.level_raw.as_str()), (Level::Warning, "40"));
+        let log = Log::from_line(r#"{"severity":3,"msg":"failed"}"#, options(LogFormat::Json, None)).unwrap();
+        assert_eq!(log.level, Level::Error);
+        assert_eq!(log.payload, "");
+    }
+
+    #[test]
+    fn payload_to_string_order() {
+        let payload: serde_json::Map<String, serde_json::Value> = serde_json::from_str(r#"{"b":1,"a":{"d":2,"c":3}}"#).unwrap();
+        assert_eq!(payload_to_string(payload.clone(), false), r#"{"b":1,"a":{"d":2,"c":3}}"#);
+        assert_eq!(payload_to_string(payload, true), r#"{"a":{"c":3,"d":2},"b":1}"#);
+    }
+
+    #[test]
+    fn payload_to_string_sizes() {
+        let map = |json: &str| serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(json).unwrap();
+        assert_eq!(payload_to_string(serde_json::Map::new(), true), "");
+        assert_eq!(payload_to_string(map(r#"{"id":7}"#), true), r#"{"id":7}"#);
+        assert_eq!(payload_to_string(map(r#"{"user":{"name":"a","id":1}}"#), true), r#"{"user":{"id":1,"name":"a"}}"#);
+        assert_eq!(payload_to_string(map(r#"{"user":{"name":"a","id":1}}"#), false), r#"{"user":{"name":"a","id":1}}"#);
+        let many: String = (0..50).rev().map(|i| format!(r#""k{i:02}":[{{"z":{i},"a":null}}]"#)).collect::<Vec<_>>().join(",");
+        let sorted = payload_to_string(map(&format!("{{{many}}}")), true);
+        assert!(sorted.starts_with(r#"{"k00":[{"a":null,"z":0}],"k01""#));
+        assert!(sorted.ends_with(r#""k49":[{"a":null,"z":49}]}"#));
+    }
+
+    #[test]
+    fn text_match_and_fold() {
+        assert!(text_match("Connection RESET", "reset", false, false));
+        assert!(text_match("anything", "", false, true));
+        assert!(!text_match("resetting", "reset", false, true));
+        assert!(text_match("was reset.", "reset", false, true));
+        assert!(!text_match("café", "cafe", false, false));
+        assert!(text_match("café", "cafe", true, false));
+        assert_eq!(fold("Straße", false), "strasse");
+        assert_eq!(fold("ΟΔΟΣ", false), "οδοσ");
+        assert_eq!(fold("Crème", true), "creme");
+    }
+
+    fn json_log(line: &str) -> Log {
+        Log::from_line(line, options(LogFormat::Json, None)).unwrap()
+    }
+
+    #[test]
+    fn query_matches() {
+        let log = json_log(r#"{"level":"ERROR","msg":"db down","caller":"db.go:12","retries":3}"#);
+        let matches = |query: &str| Query::parse(query).unwrap().matches(&log, false);
+        assert!(matches("level:error AND caller:db*"));
+        assert!(matches("down OR nothing"));
+        assert!(matches("payload.retries>2"));
+        assert!(!matches("NOT msg:down"));
+    }
+
+    #[test]
+    fn query_quoting() {
+        let log = json_log(r#"{"level":"INFO","msg":"said \"hi\" to db","caller":"api.go:3"}"#);
+        let matches = |query: &str| Query::parse(query).unwrap().matches(&log, false);
+        assert!(matches(r#""to db""#));
+        assert!(matches(r#"msg:"said \"hi\"""#));
+        assert!(!matches(r#""db to""#));
+        assert!(matches("msg:/^said .* db$/"));
+        assert!(!matches("msg:/^db/"));
+    }
+
+    #[test]
+    fn query_negation_and_grouping() {
+        let log = json_log(r#"{"level":"WARN","msg":"slow query","caller":"db.go:40"}"#);
+        let matches = |query: &str| Query::parse(query).unwrap().matches(&log, false);
+        assert!(matches("NOT fast"));
+        assert!(!matches("NOT slow"));
+        assert!(matches("NOT NOT slow"));
+        assert!(matches("not (fast OR api) AND slow"));
+        assert!(!matches("slow AND NOT (query OR fast)"));
+        // Terms without AND between them still need both, AND binds tighter than OR.
+        assert!(matches("slow query"));
+        assert!(matches("fast AND api OR slow"));
+    }
+
+    #[test]
+    fn query_level_terms() {
+        let log = json_log(r#"{"level":"WARN","msg":"slow"}"#);
+        let matches = |query: &str| Query::parse(query).unwrap().matches(&log, false);
+        assert!(matches("level:warning"));
+        assert!(matches("lvl:WARN"));
+        assert!(!matches("level:error"));
+        assert!(matches("level>=info"));
+        assert!(matches("level<error"));
+        assert!(!matches("level>warn"));
+    }
+
+    #[test]
+    fn query_field_terms() {
+        let log = json_log(r#"{"level":"INFO","msg":"done","caller":"db.go:12","retries":3,"user":{"name":"Ann"}}"#);
+        let matches = |query: &str| Query::parse(query).unwrap().matches(&log, false);
+        assert!(matches("caller:db.go*"));
+        assert!(matches("caller:d?.go*"));
+        assert!(!matches("caller:api*"));
+        assert!(matches("payload:retries"));
+        assert!(matches("payload.user.name=ann"));
+        assert!(!matches("payload.user.name=an"));
+        assert!(matches("payload.retries<=3 AND payload.retries>2"));
+        assert!(!matches("payload.missing:x"));
+        // Numeric comparisons need numbers on both sides.
+        assert!(!matches("payload.user.name>1"));
+    }
+
+    #[test]
+    fn query_equality_folds_like_other_matches() {
+        let log = json_log(r#"{"level":"INFO","msg":"x","city":"Straße","name":"Zoë"}"#);
+        let matches = |query: &str, accents: bool| Query::parse(query).unwrap().matches(&log, accents);
+        assert!(matches("payload.city=STRASSE", false));
+        assert!(!matches("payload.name=zoe", false));
+        assert!(matches("payload.name=zoe", true));
+        assert!(matches("payload.name=ZOË", false));
+    }
+
+    #[test]
+    fn query_malformed() {
+        let error = |query: &str| Query::parse(query).err().unwrap_or_default();
+        assert_eq!(error(r#""open"#), "unterminated quote");
+        assert_eq!(error("msg:/open"), "unterminated regex");
+        assert_eq!(error("(a OR b"), "missing ')'");
+        assert_eq!(error("a)"), "unexpected ')'");
+        assert_eq!(error("a AND"), "unexpected end of query");
+        assert_eq!(error(":a"), "unexpected operator");
+        assert_eq!(error("msg:"), "missing value for 'msg'");
+        assert_eq!(error("color:red"), "unknown field 'color'");
+        assert_eq!(error("payload.:x"), "unknown field 'payload.'");
+        assert!(Query::parse("msg:/(/").is_err());
+    }
+
+    #[test]
+    fn json_path_matches() {
+        let payload = r#"{"request":{"host":"api","sizes":[1,5]}}"#;
+        let matches = |path: &str| JsonPath::parse(path).unwrap().matches(payload);
+        assert!(matches("$.request.host == api"));
+        assert!(matches("$.request.sizes[1] > 4"));
+        assert!(!matches("$.request.port"));
+        assert!(!JsonPath::parse("$.request").unwrap().matches("not json"));
+    }
+
+    #[test]
+    fn json_path_nested_keys_and_indexes() {
+        let payload = r#"{"request":{"headers":{"x-id":"7"},"items":[{"sku":"a"},{"sku":"b","qty":2}]}}"#;
+        let matches = |path: &str| JsonPath::parse(path).unwrap().matches(payload);
+        assert!(matches("$.request.headers"));
+        assert!(matches("$['request'][\"headers\"]['x-id'] == \"7\""));
+        assert!(matches("$.request.items[1].sku == b"));
+        assert!(matches("$.request.items[0].sku != b"));
+        assert!(matches("$.request.items[*].qty >= 2"));
+        assert!(matches("$.request.*.x-id"));
+        assert!(matches("$..qty == 2"));
+        assert!(!matches("$..qty < 2"));
+    }
+
+    #[test]
+    fn json_path_missing_keys() {
+        let payload = r#"{"request":{"items":[{"sku":"a"}]}}"#;
+        let matches = |path: &str| JsonPath::parse(path).unwrap().matches(payload);
+        assert!(!matches("$.response"));
+        assert!(!matches("$.request.items[3]"));
+        assert!(!matches("$.request.items.sku"));
+        assert!(!matches("$.request[0]"));
+        assert!(!matches("$..qty"));
+        // A comparison on a missing key is false, also `!=`.
+        assert!(!matches("$.response != 1"));
+    }
+
+    #[test]
+    fn json_path_bad_syntax() {
+        let error = |path: &str| JsonPath::parse(path).err().unwrap_or_default();
+        assert_eq!(error("request.host"), "a path starts with $");
+        assert_eq!(error("$."), "expected a key after .");
+        assert_eq!(error("$.."), "expected a key after ..");
+        assert_eq!(error("$.items[0"), "missing ]");
+        assert_eq!(error("$.items[x]"), "unexpected [x]");
+        assert_eq!(error("$.host ~ api"), "expected a comparison at ~ api");
+        assert_eq!(error("$.host =="), "expected a value");
+    }
+
+    #[test]
+    fn detect_format() {
+        let lines = |text: &str| text.lines().map(str::to_string).collect::<Vec<_>>().into_iter();
+        assert_eq!(LogFormat::detect(lines("# started\n{\"msg\":\"a\"}\n{\"msg\":\"b\"}")), LogFormat::Json);
+        assert_eq!(LogFormat::detect(lines("level=info msg=a\nlevel=warn msg=b")), LogFormat::Logfmt);
+        assert_eq!(LogFormat::detect(lines("INFO started\nWARN slow")), LogFormat::Text);
+        assert_eq!(LogFormat::detect(lines("")), LogFormat::Json);
+    }
+
+    #[test]
+    fn logfmt_pairs_quotes_and_bare_words() {
+        let pairs = logfmt_pairs(r#"a=1 msg="say \"hi\" now" flag b="""#);
+        let expected = [("a", Some("1")), ("msg", Some(r#"say "hi" now"#)), ("flag", None), ("b", Some(""))];
+        assert_eq!(pairs, expected.map(|(key, value)| (key.to_string(), value.map(str::to_string))));
+        assert!(is_logfmt("a=1 b=2 word"));
+        assert!(!is_logfmt("a=1 some plain words"));
+    }
+}
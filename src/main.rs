@@ -1,63 +1,823 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide a console window on Windows in release
 
-use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 
-use chrono::prelude::{DateTime, Local};
+use chrono::prelude::{DateTime, FixedOffset, TimeZone, Utc};
 use eframe::egui;
 use serde::{Deserialize, Serialize};
 
+mod log;
+
+use log::{format_time, is_logfmt, level_match, logfmt_pairs, payload_value, terms_match, text_fields, text_match, CustomLevel, JsonPath, Level, Log, LogFormat, ParseOptions, Query, TimeFormat, TimePrecision};
+
 fn main() -> Result<(), eframe::Error> {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
+    let config = Config::load();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--export") {
+        let result = ExportArgs::parse(&args).map_err(|err| format!("{}\n\n{}", err, EXPORT_USAGE)).and_then(|args| export_headless(args, &config));
+        match result {
+            Ok(rows) => eprintln!("Exported {} rows", rows),
+            Err(err) => {
+                eprintln!("lvx: {}", err);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    let zoom = env_setting("LVX_ZOOM", "a scale from 0.5 to 4", |value| value.parse::<f32>().ok().filter(|zoom| (0.5..=4.0).contains(zoom)));
+    let theme = env_setting("LVX_THEME", "dark or light", |value| match value.to_lowercase().as_str() {
+        "dark" => Some(Theme::Dark),
+        "light" => Some(Theme::Light),
+        _ => None,
+    });
     let options = eframe::NativeOptions {
         follow_system_theme: false,
-        default_theme: eframe::Theme::Dark,
+        default_theme: match theme.or(config.theme) {
+            Some(Theme::Light) => eframe::Theme::Light,
+            _ => eframe::Theme::Dark,
+        },
         viewport: egui::ViewportBuilder::default().with_inner_size([480.0, 480.0]),
         ..Default::default()
     };
     eframe::run_native(
         "LVX - Log Viewer",
         options,
-        Box::new(|cc| {
+        Box::new(move |cc| {
             egui_extras::install_image_loaders(&cc.egui_ctx);
+            if let Some(zoom) = zoom {
+                cc.egui_ctx.set_zoom_factor(zoom);
+            }
 
-            Box::<App>::default()
+            Box::new(App::new(cc, config))
         }),
     )
 }
 
+const EXPORT_USAGE: &str = "Usage: lvx --export <out.csv|out.ndjson> [--filter-level <level>] [--filter-message <text>]
+           [--filter-payload <text>] [--filter-caller <text>] [--query <query>] [--utc] <input.log>
+
+Writes the rows of input.log passing the filter and exits without opening a window.
+--filter-level keeps that level and the ones above it, rows without a level and lines
+that could not be parsed are left out. Times are written in local time
+unless --utc is given or lvx.toml sets utc_time, with the precision lvx.toml sets.";
+
+/// Flags of the headless export, without `--export` the window opens as usual.
+struct ExportArgs {
+    input: String,
+    output: String,
+    format: ExportFormat,
+    min_level: Option<Level>,
+    message: String,
+    payload: String,
+    caller: String,
+    query: String,
+    utc: bool,
+}
+
+impl ExportArgs {
+    fn parse(args: &[String]) -> Result<ExportArgs, String> {
+        let mut export = ExportArgs {
+            input: "".to_string(),
+            output: "".to_string(),
+            format: ExportFormat::Csv,
+            min_level: None,
+            message: "".to_string(),
+            payload: "".to_string(),
+            caller: "".to_string(),
+            query: "".to_string(),
+            utc: false,
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().cloned().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--export" => export.output = value()?,
+                "--filter-level" => {
+                    let name = value()?;
+                    let level = Level::from_name(&name.to_uppercase());
+                    if level == Level::Unknown {
+                        return Err(format!("Unknown level {}", name));
+                    }
+                    export.min_level = Some(level);
+                }
+                "--filter-message" => export.message = value()?,
+                "--filter-payload" => export.payload = value()?,
+                "--filter-caller" => export.caller = value()?,
+                "--query" => export.query = value()?,
+                "--utc" => export.utc = true,
+                flag if flag.starts_with("--") => return Err(format!("Unknown flag {}", flag)),
+                _ if !export.input.is_empty() => return Err("Only one input file can be exported".to_string()),
+                input => export.input = input.to_string(),
+            }
+        }
+        if export.input.is_empty() {
+            return Err("No input file".to_string());
+        }
+        if export.output.to_lowercase().ends_with(".ndjson") || export.output.to_lowercase().ends_with(".jsonl") {
+            export.format = ExportFormat::Ndjson;
+        }
+        Ok(export)
+    }
+}
+
+/// Reads and filters `args.input` like a tab would and writes the rows left, returns
+/// how many were written.
+fn export_headless(args: ExportArgs, config: &Config) -> Result<usize, String> {
+    if std::fs::metadata(&args.input).is_err() {
+        return Err(format!("Cannot read {}", args.input));
+    }
+    let custom_levels = config.custom_levels.iter().flatten().filter(|custom| !custom.name.trim().is_empty()).cloned().map(std::sync::Arc::new).collect();
+    let mut tab = Tab {
+        picked_path: args.input,
+        text_pattern: config.text_pattern.as_deref().and_then(|pattern| regex::Regex::new(pattern).ok()).filter(|_| config.text_pattern.as_deref() != Some("")),
+        custom_levels,
+        ..Default::default()
+    };
+    tab.read_file();
+    if let Some(min_level) = args.min_level {
+        [tab.filter_level_debug, tab.filter_level_info, tab.filter_level_warning, tab.filter_level_error, tab.filter_level_panic] =
+            [Level::Debug, Level::Info, Level::Warning, Level::Error, Level::Panic].map(|level| level >= min_level);
+    }
+    tab.filter_message = args.message;
+    tab.filter_payload = args.payload;
+    tab.filter_caller = args.caller;
+    tab.filter_query = args.query;
+    tab.filter();
+    if let Err(err) = &tab.filter_compiled {
+        return Err(format!("Invalid query: {}", err));
+    }
+    // The window keeps rows without a level whatever the level filter, asking for a level
+    // here means only rows that have one.
+    if args.min_level.is_some() {
+        tab.filtered_logs.retain(|log| log.level != Level::Unknown);
+    }
+    let rows = tab.filtered_len();
+    let file = File::create(&args.output).map_err(|err| format!("Cannot write {}: {}", args.output, err))?;
+    let mut writer = BufWriter::new(file);
+    let time_format = TimeFormat {
+        utc: args.utc || config.utc_time == Some(true),
+        precision: config.time_precision.unwrap_or(TimePrecision::Auto),
+        ..Default::default()
+    };
+    write_export(&mut writer, (0..rows).filter_map(|row_index| tab.row_log(row_index)), args.format, time_format)
+        .and_then(|_| writer.flush())
+        .map_err(|err| format!("Cannot write {}: {}", args.output, err))?;
+    Ok(rows)
+}
+
 struct App {
-    picked_path: Option<String>,
+    tabs: Vec<Tab>,
+    active_tab: usize,
+    split_tab: Option<usize>,
+    split_sync: bool,
+    split_synced_row: Option<usize>,
+    diff_rows: Option<Vec<DiffRow>>,
+    last_follow_poll: std::time::Instant,
+    settings: Settings,
+    config: Config,
+    table_generation: usize,
+    url_input: String,
+    download: Option<Download>,
+    download_error: Option<String>,
+    large_file: Option<LargeFile>,
+    /// `Settings::last_preset` is still to be applied to the first file opened.
+    restore_preset: bool,
+    /// A short note shown in the corner until `TOAST_DURATION` has passed.
+    toast: Option<(String, std::time::Instant)>,
+}
+
+/// A file above `LARGE_FILE_SIZE` waiting for the user to pick how much of it to load.
+struct LargeFile {
+    path: String,
+    size: u64,
+    /// Opens into the active tab instead of a new one.
+    replace: bool,
+    tail_mb: u64,
+    head_lines: usize,
+}
+
+/// Loads only part of a file.
+#[derive(Clone, Copy)]
+enum Sample {
+    /// The last bytes of the file, starting at the first full line.
+    Tail(u64),
+    /// The first lines of the file.
+    Head(usize),
+}
+
+/// Lines read from a named pipe on a thread. The reader stays open when a writer goes
+/// away and waits for the next one.
+struct Pipe {
+    path: String,
+    lines: std::sync::mpsc::Receiver<Vec<u8>>,
+    /// Set when the tab lets go of the pipe, the reader thread ends at its next poll.
+    closed: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl Pipe {
+    fn open(path: &str) -> Pipe {
+        let (sender, lines) = std::sync::mpsc::channel();
+        let closed = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let thread_path = path.to_string();
+        let thread_closed = closed.clone();
+        std::thread::spawn(move || {
+            // Opened without waiting for a writer, so a tab closed before one connects does
+            // not leave the thread blocked in the open.
+            let Ok(file) = open_nonblocking(&thread_path) else {
+                return;
+            };
+            let mut reader = BufReader::new(file);
+            let mut bytes = Vec::new();
+            while !thread_closed.load(std::sync::atomic::Ordering::Relaxed) {
+                match reader.read_until(b'\n', &mut bytes) {
+                    // End of file while no writer is connected.
+                    Ok(0) => std::thread::sleep(PIPE_POLL_INTERVAL),
+                    // A line, or the last one of a writer that went away without a newline.
+                    Ok(_) => {
+                        if sender.send(std::mem::take(&mut bytes)).is_err() {
+                            return;
+                        }
+                    }
+                    // A writer is connected but wrote nothing yet, a started line is kept.
+                    Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(PIPE_POLL_INTERVAL),
+                    Err(_) => return,
+                }
+            }
+        });
+        Pipe { path: path.to_string(), lines, closed }
+    }
+}
+
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        self.closed.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// One of the files of a merged tab and how far it was read.
+struct MergeSource {
+    path: String,
+    read_offset: u64,
+    lines_read: usize,
+    file_size: u64,
+}
+
+#[cfg(unix)]
+fn is_fifo(path: &str) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &str) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn open_nonblocking(path: &str) -> std::io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(path)
+}
+
+#[cfg(not(unix))]
+fn open_nonblocking(path: &str) -> std::io::Result<File> {
+    File::open(path)
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// A log fetched over HTTP(S) on a worker thread, opened in a new tab when done.
+struct Download {
+    events: std::sync::mpsc::Receiver<DownloadEvent>,
+    received: u64,
+    total: Option<u64>,
+}
+
+enum DownloadEvent {
+    Progress { received: u64, total: Option<u64> },
+    Done(Result<std::path::PathBuf, String>),
+}
+
+/// A CSV or NDJSON export written on a worker thread.
+struct ExportJob {
+    events: std::sync::mpsc::Receiver<ExportEvent>,
+    written: usize,
+    total: usize,
+}
+
+enum ExportEvent {
+    Progress(usize),
+    Done(Result<std::path::PathBuf, String>),
+}
+
+struct Tab {
+    picked_path: String,
     logs: Vec<Log>,
-    filtered_logs: Vec<Log>,
+    filtered_logs: Vec<std::sync::Arc<Log>>,
     filter_level_debug: bool,
     filter_level_info: bool,
     filter_level_warning: bool,
     filter_level_error: bool,
     filter_level_panic: bool,
+    /// Names of the custom levels the filter leaves out.
+    filter_hidden_levels: Vec<String>,
     filter_message: String,
     filter_payload: String,
     filter_caller: String,
+    filter_source: String,
+    filter_query: String,
+    filter_matches: Option<SearchTerms>,
+    /// Rows pass when any of the set criteria match instead of all of them.
+    filter_any: bool,
+    filter_stacktrace: bool,
+    filter_parse_errors: ParseErrorFilter,
+    filter_compiled: Result<Option<Query>, String>,
+    /// The payload filter is a JSONPath expression instead of plain text.
+    filter_payload_path: bool,
+    filter_payload_compiled: Result<Option<JsonPath>, String>,
+    filter_pending: Option<std::time::Instant>,
+    filter_preview: Option<(&'static str, usize)>,
     search_founds: Vec<usize>,
     search_found_cursor: usize,
-    search_found_scroll_row: Option<usize>,
+    /// Stepping through matches only moves the highlight, the table keeps its scroll position.
+    search_keep_scroll: bool,
+    scroll_to_row: Option<usize>,
     search_level_debug: bool,
     search_level_info: bool,
     search_level_warning: bool,
     search_level_error: bool,
     search_level_panic: bool,
+    /// Names of the custom levels the search looks for.
+    search_custom_levels: Vec<String>,
     search_message: String,
     search_payload: String,
     search_caller: String,
+    search_source: String,
+    search_time: String,
+    /// The format of the Time column, what a Time search matches against and exports write.
+    time_format: TimeFormat,
     selection: std::collections::HashSet<usize>,
+    visible_rows: std::ops::Range<usize>,
+    restore_row: Option<usize>,
+    filter_applied: FilterState,
+    filter_undo_stack: Vec<FilterState>,
+    filter_redo_stack: Vec<FilterState>,
+    read_offset: u64,
+    follow: bool,
+    /// Reads appended lines like `follow` without scrolling to them.
+    auto_poll: bool,
+    follow_unseen: usize,
+    text_pattern: Option<regex::Regex>,
+    custom_levels: Vec<std::sync::Arc<CustomLevel>>,
+    format: LogFormat,
+    format_override: Option<LogFormat>,
+    head_lines: Vec<String>,
+    indexed_mode: bool,
+    indexed: Option<IndexedFile>,
+    /// Set while `picked_path` is a named pipe, its lines arrive through `follow_poll`.
+    pipe: Option<Pipe>,
+    /// Files read into this tab together, `picked_path` then only names them.
+    sources: Vec<MergeSource>,
+    search_scan: Option<usize>,
+    /// File line of the match the cursor was on when the running search started, the
+    /// cursor moves back to it when it matches again.
+    search_cursor_line: Option<usize>,
+    file_size: u64,
+    /// Identity of the file read, a different one at the same path means it was rotated.
+    file_id: Option<u64>,
+    /// Rows held before and read after a rotation found by `follow_poll`, taken by the app for a notice.
+    rotated: Option<(usize, usize)>,
+    /// Outcome of a file operation of this tab, taken by the app for a toast.
+    notice: Option<String>,
+    /// Filter and search fields in the order Tab moves focus through them, rebuilt each frame.
+    focus_chain: Vec<egui::Id>,
+    /// When polling last found the file truncated or replaced, cleared by reading it again.
+    rotated_at: Option<std::time::Instant>,
+    lines_read: usize,
+    lines_skipped: usize,
+    lines_repaired: usize,
+    /// Oldest rows dropped while following to stay within `Settings::keep_rows`.
+    lines_dropped: usize,
+    parse_duration: std::time::Duration,
+    memory_estimate: usize,
+    detail: Option<Log>,
+    sort: Option<Sort>,
+    fold_accents: bool,
+    whole_word: bool,
+    levels_before_errors_only: Option<[bool; 5]>,
+    goto_input: String,
+    goto_row: Option<usize>,
+    goto_note: Option<String>,
+    goto_hidden_line: Option<usize>,
+    sort_payload: bool,
+    skip_comments: bool,
+    /// Rows kept in view above the table, identified by their line.
+    pinned: Vec<Log>,
+    context_lines: usize,
+    find_open: bool,
+    find_focus: bool,
+    /// Scroll to the first match found after the find text changed.
+    find_jump: bool,
+    groups_open: bool,
+    /// Dotted payload key the filtered rows are grouped by.
+    group_key: String,
+    groups: Vec<LogGroup>,
+    plot_open: bool,
+    /// Dotted payload key whose numeric value is plotted over time.
+    plot_key: String,
+    /// Seconds since the epoch and the value of the filtered rows having both, in time order.
+    plot_points: Vec<[f64; 2]>,
+    /// Shows each distinct level, message and caller once instead of the rows.
+    unique_open: bool,
+    uniques: Vec<UniqueLog>,
+    /// Lines appended while following and when they arrived, in line order.
+    arrived: Vec<(usize, std::time::Instant)>,
+    /// Part of the file that is loaded, line numbers count from the start of a tail.
+    sample: Option<Sample>,
+    /// Shows or hides the Payload column, `None` hides it while every payload is empty.
+    payload_column: Option<bool>,
+    payloads_empty: bool,
+    /// Pretty printed line count of each payload by log line, for `Settings::expand_payloads`.
+    payload_lines: std::collections::HashMap<usize, usize>,
+    /// Distinct callers of the loaded rows with their counts, most frequent first.
+    callers: Vec<(String, usize)>,
+    preset_name: String,
+    export_job: Option<ExportJob>,
+    /// Line and time of the last clicked row, the center of `Settings::time_window`.
+    time_anchor: Option<(usize, DateTime<FixedOffset>)>,
 }
 
+const COLUMN_MAX_WIDTH: f32 = 600.0;
+const FILTER_HISTORY_LIMIT: usize = 50;
+const HEAD_LINES: usize = 5;
+const REPORT_TOP: usize = 10;
+const GROUP_ROWS_SHOWN: usize = 200;
+const RECENT_FILES_LIMIT: usize = 10;
+/// Files above this size ask whether to load only their head or tail.
+const LARGE_FILE_SIZE: u64 = 200 * 1024 * 1024;
+const SEARCH_FRAME_BUDGET: std::time::Duration = std::time::Duration::from_millis(8);
+/// How often a pipe without new lines is checked again.
+const PIPE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+const FILTER_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(300);
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+const ARRIVED_HIGHLIGHT: std::time::Duration = std::time::Duration::from_secs(1);
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(4);
+/// Rows between progress updates of a background export.
+const EXPORT_PROGRESS_ROWS: usize = 10_000;
+/// Most filtered rows "Copy all filtered" puts on the clipboard, more are better exported.
+const COPY_ALL_MAX_ROWS: usize = 50_000;
+const RESET_FILTER_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT), egui::Key::F);
+const RESET_SEARCH_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT), egui::Key::S);
 
-impl Default for App {
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct Settings {
+    column_widths: Vec<f32>,
+    utc_time: bool,
+    alert_enabled: bool,
+    alert_level: Level,
+    alert_beep: bool,
+    text_pattern: String,
+    editor_enabled: bool,
+    editor_command: String,
+    freeze_columns: bool,
+    time_precision: TimePrecision,
+    /// Shows the `#` column with each row's position in load order.
+    source_index: bool,
+    /// Timestamps are shown as written in the file, sorting still uses the parsed time.
+    time_verbatim: bool,
+    /// The detail panel sits right of the table instead of below it.
+    detail_side: bool,
+    /// Height of the detail panel below the table, or its width beside it.
+    detail_size: f32,
+    /// Tints the rows within `time_window_secs` of the last clicked row.
+    time_window: bool,
+    time_window_secs: f32,
+    /// UTC and precision the Time column width was last sized for, another format sizes it again.
+    time_width_format: Option<(bool, TimePrecision)>,
+    payload_highlight: bool,
+    last_dir: Option<String>,
+    density: Density,
+    /// Table cells use the monospace font instead of the body font.
+    monospace: bool,
+    payload_max_chars: usize,
+    message_highlight: bool,
+    relative_time: bool,
+    delta_time: bool,
+    /// Renders every payload pretty printed across several lines, rows grow to fit.
+    expand_payloads: bool,
+    recent_files: Vec<String>,
+    click_action: ClickAction,
+    double_click_detail: bool,
+    /// The sort last picked in a table header, applied to newly opened files.
+    sort: Option<Sort>,
+    /// Applied to newly opened files while no sort was picked.
+    default_sort: Option<Sort>,
+    skip_comments: bool,
+    /// Seconds between checks for appended lines while following or auto-polling.
+    poll_interval: f32,
+    /// Following keeps only the most recent `keep_rows_max` rows.
+    keep_rows: bool,
+    keep_rows_max: usize,
+    custom_levels: Vec<CustomLevel>,
+    /// URLs and `file:line` references in messages and payloads are clickable.
+    linkify: bool,
+    filter_presets: Vec<FilterPreset>,
+    /// The preset applied last, applied again to the first file opened after a restart.
+    last_preset: Option<String>,
+}
+
+impl Settings {
+    fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f32(self.poll_interval.max(0.1))
+    }
+
+    fn keep_rows(&self) -> Option<usize> {
+        self.keep_rows.then_some(self.keep_rows_max.max(1))
+    }
+
+    fn time_format(&self) -> TimeFormat {
+        TimeFormat { verbatim: self.time_verbatim, utc: self.utc_time, precision: self.time_precision }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            column_widths: vec![200.0, 50.0, 100.0, 100.0, 100.0],
+            utc_time: false,
+            alert_enabled: false,
+            alert_level: Level::Error,
+            alert_beep: false,
+            text_pattern: "".to_string(),
+            editor_enabled: false,
+            editor_command: "$EDITOR".to_string(),
+            freeze_columns: true,
+            time_precision: TimePrecision::Auto,
+            time_width_format: None,
+            source_index: false,
+            time_verbatim: false,
+            detail_side: false,
+            detail_size: 200.0,
+            time_window: false,
+            time_window_secs: 2.0,
+            payload_highlight: false,
+            last_dir: None,
+            density: Density::Normal,
+            monospace: false,
+            payload_max_chars: 500,
+            message_highlight: false,
+            relative_time: false,
+            delta_time: false,
+            expand_payloads: false,
+            recent_files: vec![],
+            click_action: ClickAction::Select,
+            double_click_detail: true,
+            sort: None,
+            default_sort: None,
+            skip_comments: false,
+            poll_interval: FOLLOW_POLL_INTERVAL.as_secs_f32(),
+            keep_rows: false,
+            keep_rows_max: 100_000,
+            custom_levels: vec![],
+            linkify: true,
+            filter_presets: vec![],
+            last_preset: None,
+        }
+    }
+}
+
+/// Reads the environment variable `name` at startup, a value `parse` rejects is reported
+/// and left out so the stored default applies.
+fn env_setting<T>(name: &str, expected: &str, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+    let parsed = parse(value.trim());
+    if parsed.is_none() {
+        eprintln!("lvx: ignoring {}={:?}, expected {}", name, value, expected);
+    }
+    parsed
+}
+
+/// Optional shared defaults from `lvx.toml` in the config directory. Values given there
+/// replace the stored settings at startup, changes in the UI still apply afterwards.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Config {
+    levels: Option<ConfigLevels>,
+    text_pattern: Option<String>,
+    utc_time: Option<bool>,
+    time_precision: Option<TimePrecision>,
+    theme: Option<Theme>,
+    column_widths: Option<Vec<f32>>,
+    custom_levels: Option<Vec<CustomLevel>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct ConfigLevels {
+    debug: bool,
+    info: bool,
+    warning: bool,
+    error: bool,
+    panic: bool,
+}
+
+impl Default for ConfigLevels {
+    fn default() -> Self {
+        ConfigLevels::from_array([true; 5])
+    }
+}
+
+impl ConfigLevels {
+    fn from_array([debug, info, warning, error, panic]: [bool; 5]) -> Self {
+        ConfigLevels { debug, info, warning, error, panic }
+    }
+
+    fn to_array(&self) -> [bool; 5] {
+        [self.debug, self.info, self.warning, self.error, self.panic]
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Theme {
+    Dark,
+    Light,
+}
+
+impl Config {
+    /// `lvx/lvx.toml` under `XDG_CONFIG_HOME`, `~/.config` or `APPDATA` on Windows.
+    fn path() -> Option<std::path::PathBuf> {
+        let dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(std::path::PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(std::path::PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config")))?;
+        Some(dir.join("lvx").join("lvx.toml"))
+    }
+
+    fn load() -> Config {
+        let Some(text) = Config::path().and_then(|path| std::fs::read_to_string(path).ok()) else {
+            return Config::default();
+        };
+        toml::from_str(&text).unwrap_or_else(|err| {
+            eprintln!("lvx.toml: {}", err);
+            Config::default()
+        })
+    }
+
+    /// Writes `lvx.toml`, returns where it was written.
+    fn save(&self) -> Result<std::path::PathBuf, String> {
+        let path = Config::path().ok_or("no config directory, set XDG_CONFIG_HOME or HOME")?;
+        let text = toml::to_string_pretty(self).map_err(|err| err.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+        }
+        std::fs::write(&path, text).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Ok(path)
+    }
+
+    fn apply(&self, settings: &mut Settings) {
+        if let Some(text_pattern) = &self.text_pattern {
+            settings.text_pattern = text_pattern.clone();
+        }
+        if let Some(utc_time) = self.utc_time {
+            settings.utc_time = utc_time;
+        }
+        if let Some(time_precision) = self.time_precision {
+            settings.time_precision = time_precision;
+        }
+        if let Some(column_widths) = self.column_widths.as_ref().filter(|widths| widths.len() == settings.column_widths.len()) {
+            settings.column_widths = column_widths.clone();
+        }
+        if let Some(custom_levels) = &self.custom_levels {
+            settings.custom_levels = custom_levels.clone();
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+struct FilterState {
+    level_debug: bool,
+    level_info: bool,
+    level_warning: bool,
+    level_error: bool,
+    level_panic: bool,
+    hidden_levels: Vec<String>,
+    message: String,
+    payload: String,
+    payload_path: bool,
+    caller: String,
+    source: String,
+    query: String,
+    matches: Option<SearchTerms>,
+    any: bool,
+    stacktrace: bool,
+    parse_errors: ParseErrorFilter,
+}
+
+/// Search terms promoted into the filter with "Filter to matches".
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct SearchTerms {
+    levels: [bool; 5],
+    #[serde(default)]
+    custom_levels: Vec<String>,
+    message: String,
+    payload: String,
+    caller: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    time: String,
+}
+
+impl SearchTerms {
+    /// `source` is the file name of a merged row, empty otherwise.
+    fn matches(&self, row: &Log, source: &str, time_format: TimeFormat, accents: bool, whole_word: bool) -> bool {
+        level_match(row, self.levels, &self.custom_levels)
+            && terms_match(row, &self.message, &self.payload, &self.caller, accents, whole_word)
+            && text_match(source, &self.source, accents, whole_word)
+            && time_match(row, &self.time, time_format, accents, whole_word)
+    }
+}
+
+/// Matches `needle` against the time as the Time column shows it with `format`, only
+/// formatting it when there is a needle.
+fn time_match(row: &Log, needle: &str, format: TimeFormat, accents: bool, whole_word: bool) -> bool {
+    needle.is_empty() || text_match(&format.display(row), needle, accents, whole_word)
+}
+
+impl Default for FilterState {
+    fn default() -> Self {
+        Self {
+            level_debug: true,
+            level_info: true,
+            level_warning: true,
+            level_error: true,
+            level_panic: true,
+            hidden_levels: vec![],
+            message: "".to_string(),
+            payload: "".to_string(),
+            payload_path: false,
+            caller: "".to_string(),
+            source: "".to_string(),
+            query: "".to_string(),
+            matches: None,
+            any: false,
+            stacktrace: false,
+            parse_errors: ParseErrorFilter::Show,
+        }
+    }
+}
+
+/// Filters saved under a name.
+#[derive(Serialize, Deserialize, Clone)]
+struct FilterPreset {
+    name: String,
+    filter: FilterState,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Profile {
+    column_widths: Vec<f32>,
+    utc_time: bool,
+    filter: FilterState,
+    /// Left unset by profiles saved before these were included, loading those keeps the current values.
+    text_pattern: Option<String>,
+    custom_levels: Option<Vec<CustomLevel>>,
+    theme: Option<Theme>,
+    time_precision: Option<TimePrecision>,
+}
+
+impl Profile {
+    fn read(path: &std::path::Path) -> Result<Profile, String> {
+        let file = File::open(path).map_err(|err| err.to_string())?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|err| err.to_string())
+    }
+
+    fn write(&self, path: &std::path::Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|err| err.to_string())?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self).map_err(|err| err.to_string())
+    }
+}
+
+
+impl Default for Tab {
     fn default() -> Self {
         Self {
-            picked_path: None,
+            picked_path: String::new(),
             logs: vec![],
             filtered_logs: vec![],
             filter_level_debug: true,
@@ -65,520 +825,5155 @@ impl Default for App {
             filter_level_warning: true,
             filter_level_error: true,
             filter_level_panic: true,
+            filter_hidden_levels: vec![],
             filter_message: "".to_string(),
             filter_payload: "".to_string(),
             filter_caller: "".to_string(),
+            filter_source: "".to_string(),
+            filter_query: "".to_string(),
+            filter_matches: None,
+            filter_any: false,
+            filter_stacktrace: false,
+            filter_parse_errors: ParseErrorFilter::Show,
+            filter_compiled: Ok(None),
+            filter_payload_path: false,
+            filter_payload_compiled: Ok(None),
+            filter_pending: None,
+            filter_preview: None,
             search_founds: vec![],
             search_found_cursor: 0,
-            search_found_scroll_row: None,
+            search_keep_scroll: false,
+            scroll_to_row: None,
             search_level_debug: false,
             search_level_info: false,
             search_level_warning: false,
             search_level_error: false,
             search_level_panic: false,
+            search_custom_levels: vec![],
             search_message: "".to_string(),
             search_payload: "".to_string(),
             search_caller: "".to_string(),
+            search_source: "".to_string(),
+            search_time: "".to_string(),
+            time_format: TimeFormat::default(),
             selection: Default::default(),
+            visible_rows: 0..0,
+            restore_row: None,
+            filter_applied: Default::default(),
+            filter_undo_stack: vec![],
+            filter_redo_stack: vec![],
+            read_offset: 0,
+            follow: false,
+            auto_poll: false,
+            follow_unseen: 0,
+            text_pattern: None,
+            custom_levels: vec![],
+            format: LogFormat::Json,
+            format_override: None,
+            head_lines: vec![],
+            indexed_mode: false,
+            indexed: None,
+            pipe: None,
+            sources: vec![],
+            search_scan: None,
+            search_cursor_line: None,
+            file_size: 0,
+            file_id: None,
+            rotated: None,
+            notice: None,
+            focus_chain: Vec::new(),
+            rotated_at: None,
+            lines_read: 0,
+            lines_skipped: 0,
+            lines_repaired: 0,
+            lines_dropped: 0,
+            parse_duration: Default::default(),
+            memory_estimate: 0,
+            detail: None,
+            sort: None,
+            fold_accents: false,
+            whole_word: false,
+            levels_before_errors_only: None,
+            goto_input: "".to_string(),
+            goto_row: None,
+            goto_note: None,
+            goto_hidden_line: None,
+            sort_payload: true,
+            skip_comments: false,
+            sample: None,
+            payload_column: None,
+            payloads_empty: false,
+            payload_lines: std::collections::HashMap::new(),
+            callers: vec![],
+            preset_name: String::new(),
+            export_job: None,
+            time_anchor: None,
+            pinned: vec![],
+            context_lines: 0,
+            find_open: false,
+            find_focus: false,
+            find_jump: false,
+            groups_open: false,
+            group_key: "".to_string(),
+            plot_open: false,
+            plot_key: "".to_string(),
+            plot_points: vec![],
+            groups: vec![],
+            unique_open: false,
+            uniques: vec![],
+            arrived: vec![],
         }
     }
 }
 
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            tabs: vec![],
+            active_tab: 0,
+            split_tab: None,
+            split_sync: false,
+            split_synced_row: None,
+            diff_rows: None,
+            last_follow_poll: std::time::Instant::now(),
+            settings: Default::default(),
+            config: Default::default(),
+            table_generation: 0,
+            url_input: "".to_string(),
+            download: None,
+            download_error: None,
+            large_file: None,
+            restore_preset: true,
+            toast: None,
+        }
+    }
+}
+
+impl App {
+    fn new(cc: &eframe::CreationContext<'_>, config: Config) -> Self {
+        let mut app = Self::default();
+        if let Some(storage) = cc.storage {
+            if let Some(mut settings) = eframe::get_value::<Settings>(storage, eframe::APP_KEY) {
+                if settings.column_widths.len() != app.settings.column_widths.len() {
+                    settings.column_widths = app.settings.column_widths.clone();
+                }
+                app.settings = settings;
+            }
+        }
+        config.apply(&mut app.settings);
+        app.config = config;
+        app
+    }
+}
+
 impl eframe::App for App {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.settings);
+    }
+
+    // Column widths are restored from `Settings`, egui's own table state must not override them.
+    fn persist_egui_memory(&self) -> bool {
+        false
+    }
+
+    // egui moves focus on Tab before `update` runs, so the filter and search fields are ordered here.
+    fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        let (Some(tab), Some(focused)) = (self.tabs.get(self.active_tab), ctx.memory(|m| m.focused())) else {
+            return;
+        };
+        let mut next = None;
+        raw_input.events.retain(|event| match event {
+            egui::Event::Key { key: egui::Key::Tab, pressed: true, modifiers, .. } if next.is_none() => {
+                next = focus_step(&tab.focus_chain, focused, modifiers.shift);
+                next.is_none()
+            }
+            _ => true,
+        });
+        if let Some(id) = next {
+            ctx.memory_mut(|m| m.request_focus(id));
+        }
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        for tab in &mut self.tabs {
+            if tab.search_step(SEARCH_FRAME_BUDGET) {
+                ctx.request_repaint();
+            }
+            match tab.export_poll() {
+                Some(Ok(path)) => self.toast = Some((format!("Exported to {}", path.display()), std::time::Instant::now())),
+                Some(Err(err)) => self.toast = Some((format!("Export failed: {}", err), std::time::Instant::now())),
+                None => {}
+            }
+            if let Some(text) = tab.notice.take() {
+                self.toast = Some((text, std::time::Instant::now()));
+            }
+            tab.arrived.retain(|(_, at)| at.elapsed() < ARRIVED_HIGHLIGHT);
+            if !tab.arrived.is_empty() {
+                ctx.request_repaint();
+            }
+            if let Some(pending) = tab.filter_pending {
+                if pending.elapsed() >= FILTER_DEBOUNCE {
+                    tab.filter();
+                } else {
+                    ctx.request_repaint_after(FILTER_DEBOUNCE - pending.elapsed());
+                }
+            }
+        }
+
+        if self.tabs.iter().any(Tab::polling) {
+            let interval = self.settings.poll_interval();
+            if self.last_follow_poll.elapsed() >= interval {
+                self.last_follow_poll = std::time::Instant::now();
+                let mut highest = None;
+                let keep = self.settings.keep_rows();
+                for tab in self.tabs.iter_mut().filter(|tab| tab.polling()) {
+                    highest = highest.max(tab.follow_poll(keep));
+                    if let Some((before, reloaded)) = tab.rotated.take() {
+                        let text = format!("{} was rotated, reloaded {} new lines ({} before)", tab.name(), reloaded, before);
+                        self.toast = Some((text, std::time::Instant::now()));
+                    }
+                }
+                if self.settings.alert_enabled && highest >= Some(self.settings.alert_level) {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(egui::UserAttentionType::Critical));
+                    if self.settings.alert_beep {
+                        if let Err(err) = play_sound(ALERT_SOUND) {
+                            self.toast = Some((format!("Playing the alert sound failed: {}", err), std::time::Instant::now()));
+                        }
+                    }
+                }
+            }
+            ctx.request_repaint_after(interval);
+        }
+
+        if !ctx.wants_keyboard_input() {
+            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+                let redo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+                if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+                    tab.filter_undo();
+                }
+                if ctx.input_mut(|i| i.consume_shortcut(&redo)) {
+                    tab.filter_redo();
+                }
+                let next_error = egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::ArrowDown);
+                let previous_error = egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::ArrowUp);
+                if ctx.input_mut(|i| i.consume_shortcut(&next_error)) {
+                    tab.goto_error(true);
+                }
+                if ctx.input_mut(|i| i.consume_shortcut(&previous_error)) {
+                    tab.goto_error(false);
+                }
+            }
+        }
+
+        if let Some(download) = &mut self.download {
+            let mut done = None;
+            for event in download.events.try_iter() {
+                match event {
+                    DownloadEvent::Progress { received, total } => {
+                        download.received = received;
+                        download.total = total;
+                    }
+                    DownloadEvent::Done(result) => done = Some(result),
+                }
+            }
+            match done {
+                Some(Ok(path)) => {
+                    self.download = None;
+                    self.open_tab(path.display().to_string());
+                }
+                Some(Err(err)) => {
+                    self.download = None;
+                    self.download_error = Some(err);
+                }
+                None => {}
+            }
+        }
+
+        let dropped: Vec<std::path::PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect());
+        for path in dropped {
+            self.open_tab(path.display().to_string());
+        }
+
+        self.large_file_ui(ctx);
+        self.toast_ui(ctx);
+
+        // Before Ctrl+F, which would match Ctrl+Shift+F as well.
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            if ctx.input_mut(|i| i.consume_shortcut(&RESET_FILTER_SHORTCUT)) {
+                tab.filter_reset();
+            }
+            if ctx.input_mut(|i| i.consume_shortcut(&RESET_SEARCH_SHORTCUT)) {
+                tab.search_reset();
+            }
+        }
+
+        let find = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F);
+        if ctx.input_mut(|i| i.consume_shortcut(&find)) {
+            if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                tab.find_open = true;
+                tab.find_focus = true;
+            }
+        }
+
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            if let Some(tab) = self.tabs.get(self.active_tab) {
+                tab.status_ui(ui);
+            }
+        });
+
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
                 if ui.button("📂 Open").clicked() {
-                    if let Some(path) = rfd::FileDialog::new().pick_file() {
-                        self.picked_path = Some(path.display().to_string());
-                        self.read_file()
+                    if let Some(path) = self.pick_log_file() {
+                        let path = path.display().to_string();
+                        if self.tabs.is_empty() {
+                            self.open_tab(path);
+                        } else if !self.defer_large_file(&path, true) {
+                            self.open_in_active_tab(path, None);
+                        }
                     }
                 }
 
-                if let Some(picked_path) = &self.picked_path.clone() {
+                ui.menu_button("🌐 URL", |ui| {
+                    self.open_url_ui(ui);
+                }).response.named("Open a log over HTTP(S)");
+
+                ui.menu_button("⚙", |ui| {
+                    self.settings_ui(ui);
+                }).response.named("Settings");
+
+                ui.menu_button("?", |ui| {
+                    shortcuts_ui(ui);
+                }).response.named("Keyboard shortcuts");
+
+                ui.menu_button("💾 Profile", |ui| {
+                    if ui.button("Export…").clicked() {
+                        ui.close_menu();
+                        self.save_profile(ui.ctx());
+                    }
+                    if ui.button("Import…").clicked() {
+                        ui.close_menu();
+                        self.load_profile(ui.ctx());
+                    }
+                });
+
+                if let Some(tab) = self.tabs.get_mut(self.active_tab) {
                     if ui.button("↺ Reload").clicked() {
-                        self.read_file();
+                        tab.read_file();
+                        self.diff_rows = None;
                     }
                     ui.horizontal(|ui| {
                         ui.label("File:");
-                        ui.monospace(picked_path);
+                        ui.monospace(&tab.picked_path);
+                        if ui.small_button("📋").named("Copy path").clicked() {
+                            ui.ctx().copy_text(tab.picked_path.clone());
+                        }
                     });
                 }
             });
 
-            if let Some(_picked_path) = &self.picked_path {
+            if !self.tabs.is_empty() {
                 ui.horizontal(|ui| {
-                    ui.menu_button("🔍", |ui| {
-                        ui.vertical(|ui| {
-                            egui::Grid::new("filter_grid")
-                                .num_columns(2)
-                                .spacing([40.0, 4.0])
-                                .striped(true)
-                                .show(ui, |ui| {
-                                    ui.strong("Filter");
-                                    ui.horizontal(|ui| {
-                                        if ui.button("✖").on_hover_text("Reset").clicked() {
-                                            self.filter_reset();
-                                        }
-                                    });
-                                    ui.end_row();
-
-                                    ui.label("Level");
-                                    ui.horizontal(|ui| {
-                                        if ui.selectable_label(self.filter_level_debug, "DEBUG").clicked() {
-                                            self.filter_level_debug = !self.filter_level_debug;
-                                            self.filter();
-                                        }
-                                        if ui.selectable_label(self.filter_level_info, "INFO").clicked() {
-                                            self.filter_level_info = !self.filter_level_info;
-                                            self.filter();
-                                        }
-                                        if ui.selectable_label(self.filter_level_warning, "WARNING").clicked() {
-                                            self.filter_level_warning = !self.filter_level_warning;
-                                            self.filter();
-                                        }
-                                        if ui.selectable_label(self.filter_level_error, "ERROR").clicked() {
-                                            self.filter_level_error = !self.filter_level_error;
-                                            self.filter();
-                                        }
-                                        if ui.selectable_label(self.filter_level_panic, "PANIC").clicked() {
-                                            self.filter_level_panic = !self.filter_level_panic;
-                                            self.filter();
-                                        }
-                                    });
-                                    ui.end_row();
-
-                                    ui.label("Message");
-                                    if ui.text_edit_singleline(&mut self.filter_message).changed() {
-                                        self.filter();
-                                    }
-                                    ui.end_row();
+                    let mut close_tab = None;
+                    for (index, tab) in self.tabs.iter().enumerate() {
+                        if ui.selectable_label(index == self.active_tab, tab.name()).on_hover_text(&tab.picked_path).clicked() {
+                            self.active_tab = index;
+                            self.diff_rows = None;
+                        }
+                        if ui.small_button("✖").named("Close").clicked() {
+                            close_tab = Some(index);
+                        }
+                        ui.separator();
+                    }
+                    if ui.button("➕").named("Open in new tab").clicked() {
+                        if let Some(path) = self.pick_log_file() {
+                            self.open_tab(path.display().to_string());
+                        }
+                    }
 
-                                    ui.label("Payload");
-                                    if ui.text_edit_singleline(&mut self.filter_payload).changed() {
-                                        self.filter();
+                    if self.tabs.len() > 1 {
+                        ui.separator();
+                        let previous_split = self.split_tab;
+                        let split_name = self.split_tab.and_then(|split| self.tabs.get(split)).map(|tab| tab.name());
+                        egui::ComboBox::from_id_source("split_tab")
+                            .selected_text(split_name.unwrap_or_else(|| "—".to_string()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.split_tab, None, "—");
+                                for (index, tab) in self.tabs.iter().enumerate() {
+                                    if index != self.active_tab {
+                                        ui.selectable_value(&mut self.split_tab, Some(index), tab.name());
                                     }
-                                    ui.end_row();
-
-                                    ui.label("Caller");
-                                    if ui.text_edit_singleline(&mut self.filter_caller).changed() {
-                                        self.filter();
+                                }
+                            })
+                            .response
+                            .on_hover_text("Split view");
+                        if self.split_tab != previous_split {
+                            self.diff_rows = None;
+                        }
+                        if self.split_tab.is_some() && ui.selectable_label(self.split_sync, "🔗").named_toggle(self.split_sync, "Sync scroll by time").clicked() {
+                            self.split_sync = !self.split_sync;
+                            self.split_synced_row = None;
+                        }
+                        if self.split_tab.is_some() && ui.selectable_label(self.diff_rows.is_some(), "±").on_hover_text("Diff by message").clicked() {
+                            self.diff_rows = match self.diff_rows {
+                                Some(_) => None,
+                                None => Some(self.diff()),
+                            };
+                        }
+                        ui.separator();
+                        if ui.button("⛙").named("Merge the open files into a new tab, interleaved by time").clicked() {
+                            self.open_merged_tab();
+                        }
+                    }
+                    if let Some(index) = close_tab {
+                        self.close_tab(index);
+                    }
+                });
+            }
+
+            match self.split_tab.filter(|&split| split != self.active_tab && split < self.tabs.len()) {
+                Some(split) if self.diff_rows.is_some() => {
+                    self.diff_ui(ui, split);
+                }
+                Some(split) => {
+                    let (left, right) = pair_mut(&mut self.tabs, self.active_tab, split);
+                    let settings = &mut self.settings;
+                    let table_generation = &mut self.table_generation;
+                    ui.columns(2, |columns| {
+                        left.pane_ui(&mut columns[0], settings, table_generation);
+                        right.pane_ui(&mut columns[1], settings, table_generation);
+                    });
+
+                    if self.split_sync && self.split_synced_row != Some(left.visible_rows.start) {
+                        self.split_synced_row = Some(left.visible_rows.start);
+                        if let Some(log) = left.filtered_logs.get(left.visible_rows.start) {
+                            right.scroll_to_row = right.row_near_time(log.time);
+                        }
+                    }
+                }
+                None => {
+                    if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+                        tab.pane_ui(ui, &mut self.settings, &mut self.table_generation);
+                    } else {
+                        self.empty_ui(ui);
+                    }
+                }
+            }
+        });
+    }
+}
+
+
+impl App {
+    fn open_tab(&mut self, path: String) {
+        if !self.defer_large_file(&path, false) {
+            self.open_sampled_tab(path, None);
+        }
+    }
+
+    fn open_sampled_tab(&mut self, path: String, sample: Option<Sample>) {
+        let mut tab = Tab {
+            picked_path: path,
+            text_pattern: regex::Regex::new(&self.settings.text_pattern).ok().filter(|_| !self.settings.text_pattern.is_empty()),
+            ..Default::default()
+        };
+        tab.sort = self.settings.sort.or(self.settings.default_sort);
+        tab.skip_comments = self.settings.skip_comments;
+        tab.sample = sample;
+        tab.custom_levels = self.custom_levels();
+        if let Some(levels) = &self.config.levels {
+            [tab.filter_level_debug, tab.filter_level_info, tab.filter_level_warning, tab.filter_level_error, tab.filter_level_panic] = levels.to_array();
+        }
+        tab.read_file();
+        if std::mem::take(&mut self.restore_preset) {
+            // A preset deleted since is forgotten and the file stays unfiltered.
+            let preset = self.settings.last_preset.as_ref().and_then(|name| self.settings.filter_presets.iter().find(|preset| preset.name == *name));
+            match preset {
+                Some(preset) => tab.apply_filter_state(preset.filter.clone()),
+                None => self.settings.last_preset = None,
+            }
+        }
+        self.remember_recent(tab.picked_path.clone());
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+    }
+
+    /// Opens the files of every tab into a new one, sorted by time and followed so that
+    /// lines appended to any of them are merged in as they arrive.
+    fn open_merged_tab(&mut self) {
+        let mut paths: Vec<String> = vec![];
+        for tab in &self.tabs {
+            let tab_paths = match tab.sources.is_empty() {
+                true if tab.pipe.is_none() => vec![tab.picked_path.clone()],
+                true => vec![],
+                false => tab.sources.iter().map(|source| source.path.clone()).collect(),
+            };
+            for path in tab_paths {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+        if paths.len() < 2 {
+            return;
+        }
+        let mut tab = Tab {
+            picked_path: paths.join(" + "),
+            sources: paths.into_iter().map(|path| MergeSource { path, read_offset: 0, lines_read: 0, file_size: 0 }).collect(),
+            text_pattern: regex::Regex::new(&self.settings.text_pattern).ok().filter(|_| !self.settings.text_pattern.is_empty()),
+            ..Default::default()
+        };
+        tab.sort = Some(Sort { column: SortColumn::Time, descending: false });
+        tab.skip_comments = self.settings.skip_comments;
+        tab.custom_levels = self.custom_levels();
+        tab.follow = true;
+        tab.read_file();
+        self.tabs.push(tab);
+        self.active_tab = self.tabs.len() - 1;
+    }
+
+    fn open_in_active_tab(&mut self, path: String, sample: Option<Sample>) {
+        let Some(tab) = self.tabs.get_mut(self.active_tab) else {
+            return;
+        };
+        tab.sort = self.settings.sort.or(self.settings.default_sort);
+        tab.sample = sample;
+        tab.open_file(path.clone());
+        self.diff_rows = None;
+        self.remember_recent(path);
+    }
+
+    /// Holds back a file above `LARGE_FILE_SIZE` until the user picks how much of it to
+    /// load, returns true when it was held back.
+    fn defer_large_file(&mut self, path: &str, replace: bool) -> bool {
+        let size = std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or_default();
+        if size <= LARGE_FILE_SIZE {
+            return false;
+        }
+        self.large_file = Some(LargeFile { path: path.to_string(), size, replace, tail_mb: 50, head_lines: 100_000 });
+        true
+    }
+
+    fn toast_ui(&mut self, ctx: &egui::Context) {
+        let Some((text, shown)) = &self.toast else {
+            return;
+        };
+        if shown.elapsed() >= TOAST_DURATION {
+            self.toast = None;
+            return;
+        }
+        ctx.request_repaint_after(TOAST_DURATION - shown.elapsed());
+        egui::Area::new(egui::Id::new("toast"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-16.0, -40.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(text.as_str());
+                });
+            });
+    }
+
+    fn large_file_ui(&mut self, ctx: &egui::Context) {
+        let Some(large_file) = &mut self.large_file else {
+            return;
+        };
+        let mut choice = None;
+        let mut cancel = false;
+        egui::Window::new("Large file")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("{} is {}, loading all of it may take a while.", large_file.path, format_bytes(large_file.size as usize)));
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Load last").clicked() {
+                        choice = Some(Some(Sample::Tail(large_file.tail_mb * 1024 * 1024)));
+                    }
+                    let max_mb = (large_file.size / (1024 * 1024)).max(1);
+                    ui.add(egui::DragValue::new(&mut large_file.tail_mb).clamp_range(1..=max_mb).suffix(" MB"));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Load first").clicked() {
+                        choice = Some(Some(Sample::Head(large_file.head_lines)));
+                    }
+                    ui.add(egui::DragValue::new(&mut large_file.head_lines).clamp_range(1..=usize::MAX).speed(1000).suffix(" lines"));
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Load everything").clicked() {
+                        choice = Some(None);
+                    }
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if cancel {
+            self.large_file = None;
+        }
+        if let Some(sample) = choice {
+            let Some(LargeFile { path, replace, .. }) = self.large_file.take() else {
+                return;
+            };
+            if replace {
+                self.open_in_active_tab(path, sample);
+            } else {
+                self.open_sampled_tab(path, sample);
+            }
+        }
+    }
+
+    fn remember_recent(&mut self, path: String) {
+        self.settings.recent_files.retain(|recent| *recent != path);
+        self.settings.recent_files.insert(0, path);
+        self.settings.recent_files.truncate(RECENT_FILES_LIMIT);
+    }
+
+    /// Shown instead of the table until a file is open.
+    fn empty_ui(&mut self, ui: &mut egui::Ui) {
+        let mut open = None;
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 4.0);
+            ui.heading("Open a log file or drop one here");
+            ui.add_space(8.0);
+            if ui.button("📂 Open…").clicked() {
+                open = self.pick_log_file().map(|path| path.display().to_string());
+            }
+            let recent: Vec<&String> = self.settings.recent_files.iter().filter(|path| std::path::Path::new(path).exists()).collect();
+            if !recent.is_empty() {
+                ui.add_space(16.0);
+                ui.strong("Recent files");
+                for path in recent {
+                    if ui.link(path).clicked() {
+                        open = Some(path.clone());
+                    }
+                }
+            }
+        });
+        if let Some(path) = open {
+            self.open_tab(path);
+        }
+    }
+
+    fn open_url_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let edit = egui::TextEdit::singleline(&mut self.url_input).hint_text("https://…").desired_width(360.0);
+            let response = ui.add_enabled(self.download.is_none(), edit);
+            let url = self.url_input.trim();
+            let valid = url.starts_with("http://") || url.starts_with("https://");
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.add_enabled(valid && self.download.is_none(), egui::Button::new("Open")).clicked() || (enter && valid) {
+                self.start_download(ui.ctx(), url.to_string());
+            }
+        });
+        if let Some(download) = &self.download {
+            let text = match download.total {
+                Some(total) => format!("{} of {}", format_bytes(download.received as usize), format_bytes(total as usize)),
+                None => format_bytes(download.received as usize),
+            };
+            let progress = download.total.filter(|&total| total > 0).map_or(0.0, |total| download.received as f32 / total as f32);
+            ui.add(egui::ProgressBar::new(progress).text(text).animate(download.total.is_none()));
+        }
+        if let Some(err) = &self.download_error {
+            ui.colored_label(egui::Color32::from_rgb(240, 60, 10), err);
+        }
+    }
+
+    fn start_download(&mut self, ctx: &egui::Context, url: String) {
+        let (sender, events) = std::sync::mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = download(&url, &sender, &ctx);
+            let _ = sender.send(DownloadEvent::Done(result));
+            ctx.request_repaint();
+        });
+        self.download = Some(Download { events, received: 0, total: None });
+        self.download_error = None;
+    }
+
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("settings_grid")
+            .num_columns(2)
+            .spacing([40.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Follow alert");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings.alert_enabled, "Enabled");
+                    egui::ComboBox::from_id_source("alert_level")
+                        .selected_text(self.settings.alert_level.to_string())
+                        .show_ui(ui, |ui| {
+                            for level in [Level::Warning, Level::Error, Level::Panic] {
+                                ui.selectable_value(&mut self.settings.alert_level, level, level.to_string());
+                            }
+                        })
+                        .response
+                        .on_hover_text("Minimum level");
+                    ui.checkbox(&mut self.settings.alert_beep, "Sound").on_hover_text(format!("Play the desktop alert sound with `{}`", ALERT_SOUND[0]));
+                });
+                ui.end_row();
+
+                ui.label("Text pattern");
+                ui.vertical(|ui| {
+                    let edit = egui::TextEdit::singleline(&mut self.settings.text_pattern)
+                        .hint_text(r"(?P<ts>\S+) (?P<level>\w+) (?P<msg>.*)")
+                        .desired_width(400.0);
+                    ui.add(edit).on_hover_text("Regex with named groups for non JSON lines");
+                    ui.horizontal(|ui| {
+                        let pattern = regex::Regex::new(&self.settings.text_pattern);
+                        if let Err(err) = &pattern {
+                            ui.colored_label(egui::Color32::from_rgb(240, 60, 10), err.to_string());
+                        }
+                        if ui.add_enabled(pattern.is_ok(), egui::Button::new("Apply")).on_hover_text("Reload open files").clicked() {
+                            self.apply_text_pattern();
+                        }
+                    });
+                    if let (Ok(pattern), Some(tab)) = (regex::Regex::new(&self.settings.text_pattern), self.tabs.get(self.active_tab)) {
+                        if !self.settings.text_pattern.is_empty() {
+                            for line in &tab.head_lines {
+                                let captures = match pattern.captures(line) {
+                                    Some(captures) => pattern
+                                        .capture_names()
+                                        .flatten()
+                                        .map(|name| format!("{}={}", name, captures.name(name).map(|m| m.as_str()).unwrap_or_default()))
+                                        .collect::<Vec<_>>()
+                                        .join("  "),
+                                    None => "no match".to_string(),
+                                };
+                                ui.monospace(captures);
+                            }
+                        }
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Custom levels");
+                ui.vertical(|ui| {
+                    let mut remove = None;
+                    for (index, custom) in self.settings.custom_levels.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut custom.name).hint_text("NOTICE").desired_width(90.0)).on_hover_text("Level text in the log, any case");
+                            ui.add(egui::TextEdit::singleline(&mut custom.label).hint_text("Label").desired_width(90.0));
+                            ui.color_edit_button_srgb(&mut custom.color);
+                            egui::ComboBox::from_id_source(("custom_level_rank", index))
+                                .selected_text(format!("like {}", custom.rank.to_string()))
+                                .show_ui(ui, |ui| {
+                                    for level in [Level::Debug, Level::Info, Level::Warning, Level::Error, Level::Panic] {
+                                        ui.selectable_value(&mut custom.rank, level, level.to_string());
                                     }
-                                    ui.end_row();
-                                });
+                                })
+                                .response
+                                .on_hover_text("Sorted and alerted on like this level");
+                            if ui.small_button("✖").named("Remove").clicked() {
+                                remove = Some(index);
+                            }
+                        });
+                    }
+                    if let Some(index) = remove {
+                        self.settings.custom_levels.remove(index);
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("➕ Add").clicked() {
+                            self.settings.custom_levels.push(CustomLevel::default());
+                        }
+                        if ui.button("Apply").on_hover_text("Reload open files").clicked() {
+                            self.apply_custom_levels();
+                        }
+                    });
+                });
+                ui.end_row();
+
+                ui.label("Open caller");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings.editor_enabled, "Click to open");
+                    let edit = egui::TextEdit::singleline(&mut self.settings.editor_command)
+                        .hint_text("code -g {file}:{line}")
+                        .desired_width(300.0);
+                    ui.add_enabled(self.settings.editor_enabled, edit)
+                        .on_hover_text("Editor command, {file} and {line} come from the caller and $VAR from the environment");
+                });
+                ui.end_row();
+
+                ui.label("Time precision");
+                egui::ComboBox::from_id_source("time_precision")
+                    .selected_text(self.settings.time_precision.name())
+                    .show_ui(ui, |ui| {
+                        for precision in [TimePrecision::Auto, TimePrecision::Seconds, TimePrecision::Millis, TimePrecision::Micros, TimePrecision::Nanos] {
+                            ui.selectable_value(&mut self.settings.time_precision, precision, precision.name());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Row density");
+                ui.horizontal(|ui| {
+                    for density in [Density::Compact, Density::Normal, Density::Comfortable] {
+                        ui.selectable_value(&mut self.settings.density, density, density.name());
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Table font");
+                ui.horizontal(|ui| {
+                    let proportional = ui.selectable_value(&mut self.settings.monospace, false, "Proportional");
+                    let monospace = ui.selectable_value(&mut self.settings.monospace, true, "Monospace").on_hover_text("Lines up numbers and fields across rows");
+                    if proportional.changed() || monospace.changed() {
+                        // Sizes the Time column again for the other font.
+                        self.settings.time_width_format = None;
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Config file");
+                ui.horizontal(|ui| {
+                    let path = Config::path();
+                    ui.monospace(path.as_ref().map(|path| path.display().to_string()).unwrap_or_default());
+                    if ui.add_enabled(path.is_some(), egui::Button::new("Write")).on_hover_text("Save the current settings as defaults").clicked() {
+                        self.write_config(ui.ctx());
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Table");
+                ui.checkbox(&mut self.settings.freeze_columns, "Freeze Time and Level columns")
+                    .on_hover_text("Keep them visible while scrolling horizontally");
+                ui.end_row();
+
+                ui.label("Comments");
+                if ui.checkbox(&mut self.settings.skip_comments, "Skip lines starting with #").changed() {
+                    for tab in &mut self.tabs {
+                        tab.skip_comments = self.settings.skip_comments;
+                        tab.read_file();
+                    }
+                    self.diff_rows = None;
+                }
+                ui.end_row();
+
+                ui.label("Default sort");
+                egui::ComboBox::from_id_source("default_sort")
+                    .selected_text(Sort::name(self.settings.default_sort))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.settings.default_sort, None, Sort::name(None));
+                        for column in [SortColumn::Time, SortColumn::Level, SortColumn::Message, SortColumn::Caller] {
+                            for descending in [false, true] {
+                                let sort = Some(Sort { column, descending });
+                                ui.selectable_value(&mut self.settings.default_sort, sort, Sort::name(sort));
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Used for new files until a column header is clicked");
+                ui.end_row();
+
+                ui.label("Row click");
+                ui.horizontal(|ui| {
+                    for action in [ClickAction::Select, ClickAction::Detail, ClickAction::Both] {
+                        ui.selectable_value(&mut self.settings.click_action, action, action.name());
+                    }
+                    ui.checkbox(&mut self.settings.double_click_detail, "Double-click opens detail");
+                });
+                ui.end_row();
+
+                ui.label("Poll interval");
+                ui.add(egui::DragValue::new(&mut self.settings.poll_interval).clamp_range(0.1..=600.0).speed(0.1).suffix(" s"))
+                    .on_hover_text("How often followed and auto-polled files are checked for appended lines");
+                ui.end_row();
+
+                ui.label("Keep rows");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.settings.keep_rows, "Limit").on_hover_text("While following, drop the oldest rows beyond the limit");
+                    ui.add_enabled(self.settings.keep_rows, egui::DragValue::new(&mut self.settings.keep_rows_max).clamp_range(1_000..=100_000_000).speed(1000.0).suffix(" rows"));
+                });
+                ui.end_row();
+
+                ui.label("Payload length");
+                ui.add(egui::DragValue::new(&mut self.settings.payload_max_chars).clamp_range(0..=100_000).suffix(" chars"))
+                    .on_hover_text("Longer payloads are cut with an ellipsis, 0 shows them in full");
+                ui.end_row();
+
+                ui.label("Message");
+                ui.checkbox(&mut self.settings.message_highlight, "Highlight JSON and key=value messages")
+                    .on_hover_text("Checks every visible message, which is slower on wide tables");
+                ui.end_row();
+
+                ui.label("Links");
+                ui.checkbox(&mut self.settings.linkify, "Open URLs and file:line references on click")
+                    .on_hover_text("file:line references open in the editor set under Open caller");
+                ui.end_row();
+            });
+    }
+
+    fn apply_custom_levels(&mut self) {
+        let custom_levels = self.custom_levels();
+        for tab in &mut self.tabs {
+            tab.custom_levels = custom_levels.clone();
+            tab.read_file();
+        }
+        self.diff_rows = None;
+    }
+
+    fn custom_levels(&self) -> Vec<std::sync::Arc<CustomLevel>> {
+        self.settings.custom_levels.iter().filter(|custom| !custom.name.trim().is_empty()).cloned().map(std::sync::Arc::new).collect()
+    }
+
+    fn apply_text_pattern(&mut self) {
+        let pattern = regex::Regex::new(&self.settings.text_pattern).ok().filter(|_| !self.settings.text_pattern.is_empty());
+        for tab in &mut self.tabs {
+            tab.text_pattern = pattern.clone();
+            tab.read_file();
+        }
+        self.diff_rows = None;
+    }
+
+    fn write_config(&mut self, ctx: &egui::Context) {
+        let levels = self.tabs.get(self.active_tab).map(|tab| tab.filter_levels()).unwrap_or([true; 5]);
+        self.config = Config {
+            levels: Some(ConfigLevels::from_array(levels)),
+            text_pattern: Some(self.settings.text_pattern.clone()),
+            utc_time: Some(self.settings.utc_time),
+            time_precision: Some(self.settings.time_precision),
+            theme: Some(if ctx.style().visuals.dark_mode { Theme::Dark } else { Theme::Light }),
+            column_widths: Some(self.settings.column_widths.clone()),
+            custom_levels: Some(self.settings.custom_levels.clone()),
+        };
+        let text = match self.config.save() {
+            Ok(path) => format!("Config saved to {}", path.display()),
+            Err(err) => format!("Saving the config failed: {}", err),
+        };
+        self.toast = Some((text, std::time::Instant::now()));
+    }
+
+    /// File dialog starting in the directory of the last opened file.
+    fn pick_log_file(&mut self) -> Option<std::path::PathBuf> {
+        let mut dialog = rfd::FileDialog::new();
+        if let Some(dir) = &self.settings.last_dir {
+            dialog = dialog.set_directory(dir);
+        }
+        let path = dialog.pick_file()?;
+        self.settings.last_dir = path.parent().map(|dir| dir.display().to_string());
+        Some(path)
+    }
+
+    fn save_profile(&mut self, ctx: &egui::Context) {
+        let profile = Profile {
+            column_widths: self.settings.column_widths.clone(),
+            utc_time: self.settings.utc_time,
+            filter: self.tabs.get(self.active_tab).map(|tab| tab.filter_state()).unwrap_or_default(),
+            text_pattern: Some(self.settings.text_pattern.clone()),
+            custom_levels: Some(self.settings.custom_levels.clone()),
+            theme: Some(if ctx.style().visuals.dark_mode { Theme::Dark } else { Theme::Light }),
+            time_precision: Some(self.settings.time_precision),
+        };
+        let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).set_file_name("lvx-profile.json").save_file() else {
+            return;
+        };
+        let text = match profile.write(&path) {
+            Ok(()) => format!("Profile saved to {}", path.display()),
+            Err(err) => format!("Saving the profile failed: {}", err),
+        };
+        self.toast = Some((text, std::time::Instant::now()));
+    }
+
+    fn load_profile(&mut self, ctx: &egui::Context) {
+        let Some(path) = rfd::FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+            return;
+        };
+        let profile = match Profile::read(&path) {
+            Ok(profile) => profile,
+            Err(err) => {
+                self.toast = Some((format!("Loading the profile failed: {}", err), std::time::Instant::now()));
+                return;
+            }
+        };
+        if profile.column_widths.len() == self.settings.column_widths.len() {
+            self.settings.column_widths = profile.column_widths;
+            self.table_generation += 1;
+        }
+        self.settings.utc_time = profile.utc_time;
+        if let Some(time_precision) = profile.time_precision {
+            self.settings.time_precision = time_precision;
+        }
+        match profile.theme {
+            Some(Theme::Dark) => ctx.set_visuals(egui::Visuals::dark()),
+            Some(Theme::Light) => ctx.set_visuals(egui::Visuals::light()),
+            None => {}
+        }
+        let reparse = profile.text_pattern.is_some() || profile.custom_levels.is_some();
+        if let Some(text_pattern) = profile.text_pattern {
+            self.settings.text_pattern = text_pattern;
+        }
+        if let Some(custom_levels) = profile.custom_levels {
+            self.settings.custom_levels = custom_levels;
+            let custom_levels = self.custom_levels();
+            for tab in &mut self.tabs {
+                tab.custom_levels = custom_levels.clone();
+            }
+        }
+        // Rereads every tab with the loaded pattern and levels before the filter is applied.
+        if reparse {
+            self.apply_text_pattern();
+        }
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            tab.apply_filter_state(profile.filter);
+        }
+        self.toast = Some((format!("Profile loaded from {}", path.display()), std::time::Instant::now()));
+    }
+
+    fn close_tab(&mut self, index: usize) {
+        self.tabs.remove(index);
+        self.diff_rows = None;
+        if self.active_tab > index || self.active_tab >= self.tabs.len() {
+            self.active_tab = self.active_tab.saturating_sub(1);
+        }
+        self.split_tab = match self.split_tab {
+            Some(split) if split == index => None,
+            Some(split) if split > index => Some(split - 1),
+            split => split,
+        };
+    }
+
+    /// Aligns the filtered rows of the active and split tabs by message and caller.
+    fn diff(&self) -> Vec<DiffRow> {
+        let (Some(left), Some(right)) = (self.tabs.get(self.active_tab), self.split_tab.and_then(|split| self.tabs.get(split))) else {
+            return vec![];
+        };
+        let key = |log: &std::sync::Arc<Log>| (log.message.clone(), log.caller.clone());
+        let old: Vec<_> = left.filtered_logs.iter().map(key).collect();
+        let new: Vec<_> = right.filtered_logs.iter().map(key).collect();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+
+        let mut rows = vec![];
+        for op in similar::capture_diff_slices_deadline(similar::Algorithm::Myers, &old, &new, Some(deadline)) {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            if tag == similar::DiffTag::Equal {
+                rows.extend(old_range.map(|index| DiffRow { kind: DiffKind::Same, index }));
+                continue;
+            }
+            rows.extend(old_range.map(|index| DiffRow { kind: DiffKind::Removed, index }));
+            rows.extend(new_range.map(|index| DiffRow { kind: DiffKind::Added, index }));
+        }
+        rows
+    }
+
+    fn diff_ui(&mut self, ui: &mut egui::Ui, split: usize) {
+        let Some(rows) = &self.diff_rows else {
+            return;
+        };
+        let left = &self.tabs[self.active_tab];
+        let right = &self.tabs[split];
+
+        ui.horizontal(|ui| {
+            let removed = rows.iter().filter(|row| row.kind == DiffKind::Removed).count();
+            let added = rows.iter().filter(|row| row.kind == DiffKind::Added).count();
+            ui.colored_label(DiffKind::Removed.color(), format!("− {} only in {}", removed, left.name()));
+            ui.colored_label(DiffKind::Added.color(), format!("+ {} only in {}", added, right.name()));
+        });
+        ui.separator();
+
+        use egui_extras::{Column, TableBuilder};
+        let text_height = egui::TextStyle::Body
+            .resolve(ui.style())
+            .size
+            .max(ui.spacing().interact_size.y);
+        let widths = &self.settings.column_widths;
+        let time_format = self.settings.time_format();
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::exact(20.0))
+            .column(Column::initial(widths[0]).at_least(100.0))
+            .column(Column::initial(widths[1]).at_least(50.0))
+            .column(Column::initial(widths[2]).at_least(100.0).clip(true))
+            .column(Column::initial(widths[4]).at_least(100.0).clip(true))
+            .min_scrolled_height(0.0)
+            .header(20.0, |mut header| {
+                header.col(|_| {});
+                header.col(|ui| {
+                    ui.strong("Time");
+                });
+                header.col(|ui| {
+                    ui.strong("Level");
+                });
+                header.col(|ui| {
+                    ui.strong("Message");
+                });
+                header.col(|ui| {
+                    ui.strong("Caller");
+                });
+            })
+            .body(|body| {
+                body.rows(text_height, rows.len(), |mut row| {
+                    let diff_row = &rows[row.index()];
+                    let log = match diff_row.kind {
+                        DiffKind::Added => &right.filtered_logs[diff_row.index],
+                        _ => &left.filtered_logs[diff_row.index],
+                    };
+                    let color = diff_row.kind.color();
+                    row.col(|ui| {
+                        ui.colored_label(color, diff_row.kind.marker());
+                    });
+                    row.col(|ui| {
+                        ui.colored_label(color, time_format.display(log));
+                    });
+                    row.col(|ui| {
+                        ui.colored_label(log.level_color(), log.level_label());
+                    });
+                    row.col(|ui| {
+                        ui.colored_label(color, &log.message);
+                    });
+                    row.col(|ui| {
+                        ui.colored_label(color, &log.caller);
+                    });
+                });
+            });
+    }
+}
+
+
+impl Tab {
+    fn name(&self) -> String {
+        if !self.sources.is_empty() {
+            return format!("Merged ({})", self.sources.len());
+        }
+        file_name(&self.picked_path)
+    }
+
+    fn filter_search_ui(&mut self, ui: &mut egui::Ui) {
+        self.focus_chain.clear();
+        ui.vertical(|ui| {
+            egui::Grid::new("filter_grid")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Filter");
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(!self.filter_undo_stack.is_empty(), egui::Button::new("⟲")).on_hover_text("Undo (Ctrl+Z)").clicked() {
+                            self.filter_undo();
+                        }
+                        if ui.add_enabled(!self.filter_redo_stack.is_empty(), egui::Button::new("⟳")).on_hover_text("Redo (Ctrl+Y)").clicked() {
+                            self.filter_redo();
+                        }
+                        if ui.button("✖").named("Reset (Ctrl+Shift+F)").clicked() {
+                            self.filter_reset();
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Level");
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(self.filter_level_debug, "DEBUG").clicked() {
+                            self.filter_level_debug = !self.filter_level_debug;
+                            self.filter();
+                        }
+                        if ui.selectable_label(self.filter_level_info, "INFO").clicked() {
+                            self.filter_level_info = !self.filter_level_info;
+                            self.filter();
+                        }
+                        if ui.selectable_label(self.filter_level_warning, "WARNING").clicked() {
+                            self.filter_level_warning = !self.filter_level_warning;
+                            self.filter();
+                        }
+                        if ui.selectable_label(self.filter_level_error, "ERROR").clicked() {
+                            self.filter_level_error = !self.filter_level_error;
+                            self.filter();
+                        }
+                        if ui.selectable_label(self.filter_level_panic, "PANIC").clicked() {
+                            self.filter_level_panic = !self.filter_level_panic;
+                            self.filter();
+                        }
+                        for custom in self.custom_levels.clone() {
+                            let hidden = self.filter_hidden_levels.contains(&custom.name);
+                            if ui.selectable_label(!hidden, egui::RichText::new(custom.label()).color(custom.color())).clicked() {
+                                toggle_name(&mut self.filter_hidden_levels, &custom.name);
+                                self.filter();
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Message");
+                    ui.horizontal(|ui| {
+                        let response = ui.text_edit_singleline(&mut self.filter_message);
+                        self.focus_chain.push(response.id);
+                        if response.changed() {
+                            self.filter_typed("message");
+                        }
+                        if clear_button(ui, &mut self.filter_message) {
+                            self.filter();
+                        }
+                        self.filter_preview_ui(ui, "message");
+                    });
+                    ui.end_row();
+
+                    ui.label("Payload");
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            let hint = if self.filter_payload_path { "$.request.headers.host == \"api\"" } else { "" };
+                            let response = ui.add(egui::TextEdit::singleline(&mut self.filter_payload).hint_text(hint));
+                            self.focus_chain.push(response.id);
+                            if response.changed() {
+                                self.filter_typed("payload");
+                            }
+                            if clear_button(ui, &mut self.filter_payload) {
+                                self.filter();
+                            }
+                            if ui.selectable_label(self.filter_payload_path, "$").named_toggle(self.filter_payload_path, "JSONPath expression").clicked() {
+                                self.filter_payload_path = !self.filter_payload_path;
+                                self.filter();
+                            }
+                            self.filter_preview_ui(ui, "payload");
+                        });
+                        if let Err(err) = &self.filter_payload_compiled {
+                            ui.colored_label(egui::Color32::from_rgb(240, 60, 10), err);
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Caller");
+                    ui.horizontal(|ui| {
+                        let response = ui.text_edit_singleline(&mut self.filter_caller);
+                        self.focus_chain.push(response.id);
+                        if response.changed() {
+                            self.filter_typed("caller");
+                        }
+                        if clear_button(ui, &mut self.filter_caller) {
+                            self.filter();
+                        }
+                        if !self.callers.is_empty() {
+                            let mut picked = None;
+                            ui.menu_button(format!("{} callers", self.callers.len()), |ui| {
+                                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                                    for (caller, count) in &self.callers {
+                                        if ui.button(format!("{} ({})", caller, count)).clicked() {
+                                            picked = Some(caller.clone());
+                                            ui.close_menu();
+                                        }
+                                    }
+                                });
+                            }).response.on_hover_text("Distinct callers in the file, pick one to filter to it");
+                            if let Some(caller) = picked {
+                                self.filter_caller = caller;
+                                self.filter();
+                            }
+                        }
+                        self.filter_preview_ui(ui, "caller");
+                    });
+                    ui.end_row();
+
+                    if !self.sources.is_empty() {
+                        ui.label("Source");
+                        ui.horizontal(|ui| {
+                            let response = ui.text_edit_singleline(&mut self.filter_source).on_hover_text("Part of the file name the merged row was read from");
+                            self.focus_chain.push(response.id);
+                            if response.changed() {
+                                self.filter_typed("source");
+                            }
+                            if clear_button(ui, &mut self.filter_source) {
+                                self.filter();
+                            }
+                            let mut picked = None;
+                            ui.menu_button(format!("{} sources", self.sources.len()), |ui| {
+                                for source in &self.sources {
+                                    if ui.button(file_name(&source.path)).on_hover_text(&source.path).clicked() {
+                                        picked = Some(file_name(&source.path));
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                            if let Some(source) = picked {
+                                self.filter_source = source;
+                                self.filter();
+                            }
+                            self.filter_preview_ui(ui, "source");
+                        });
+                        ui.end_row();
+                    }
+
+                    ui.label("Query");
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            let edit = egui::TextEdit::singleline(&mut self.filter_query).hint_text("level:error AND caller:db* NOT payload.retries<3");
+                            let response = ui.add(edit);
+                            self.focus_chain.push(response.id);
+                            if response.changed() {
+                                self.filter_typed("query");
+                            }
+                            self.filter_preview_ui(ui, "query");
+                        });
+                        if let Err(err) = &self.filter_compiled {
+                            ui.colored_label(egui::Color32::from_rgb(240, 60, 10), err);
+                        }
+                    });
+                    ui.end_row();
+
+                    if self.filter_matches.is_some() {
+                        ui.label("Matches");
+                        ui.horizontal(|ui| {
+                            ui.weak("Search terms are applied");
+                            if ui.small_button("✖").named("Remove").clicked() {
+                                self.filter_matches = None;
+                                self.filter();
+                            }
+                        });
+                        ui.end_row();
+                    }
+
+                    ui.label("Stack trace");
+                    if ui.checkbox(&mut self.filter_stacktrace, "Only rows with a stack trace").changed() {
+                        self.filter();
+                    }
+                    ui.end_row();
+
+                    ui.label("Unparsed lines");
+                    ui.horizontal(|ui| {
+                        let mut changed = false;
+                        for option in [ParseErrorFilter::Show, ParseErrorFilter::Hide, ParseErrorFilter::Only] {
+                            changed |= ui.selectable_value(&mut self.filter_parse_errors, option, option.name()).changed();
+                        }
+                        if changed {
+                            self.filter();
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Combine");
+                    ui.horizontal(|ui| {
+                        let all = ui.selectable_value(&mut self.filter_any, false, "AND").on_hover_text("Rows must match every criterion");
+                        let any = ui.selectable_value(&mut self.filter_any, true, "OR").on_hover_text("Rows must match at least one of the criteria set");
+                        if all.changed() || any.changed() {
+                            self.filter();
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Options");
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut self.fold_accents, "Ignore accents").on_hover_text("Also applies to search").changed() {
+                            self.filter();
+                        }
+                        if ui.checkbox(&mut self.whole_word, "Whole words").on_hover_text("Message, Payload and Caller only match whole words, also in search").changed() {
+                            self.filter();
+                        }
+                    });
+                    ui.end_row();
+                });
+
+            ui.separator();
+            egui::Grid::new("search_grid")
+                .num_columns(2)
+                .spacing([40.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Search");
+                    ui.horizontal(|ui| {
+                        if ui.button("🔝").named("First").clicked() {
+                            self.search_first();
+                        }
+                        if ui.button("⬅").named("Previous").clicked() {
+                            self.search_previous();
+                        }
+                        if ui.button("➡").named("Next").clicked() {
+                            self.search_next();
+                        }
+                        if ui.button("🔚").named("Last").clicked() {
+                            self.search_last();
+                        }
+                        if ui.button("✖").named("Reset (Ctrl+Shift+S)").clicked() {
+                            self.search_reset();
+                        }
+                        if ui.add_enabled(self.search_active(), egui::Button::new("Filter to matches")).on_hover_text("Keep only the rows matching the search").clicked() {
+                            self.filter_to_matches();
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Level");
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(self.search_level_debug, "DEBUG").clicked() {
+                            self.search_level_debug = !self.search_level_debug;
+                            self.search();
+                        }
+                        if ui.selectable_label(self.search_level_info, "INFO").clicked() {
+                            self.search_level_info = !self.search_level_info;
+                            self.search();
+                        }
+                        if ui.selectable_label(self.search_level_warning, "WARNING").clicked() {
+                            self.search_level_warning = !self.search_level_warning;
+                            self.search();
+                        }
+                        if ui.selectable_label(self.search_level_error, "ERROR").clicked() {
+                            self.search_level_error = !self.search_level_error;
+                            self.search();
+                        }
+                        if ui.selectable_label(self.search_level_panic, "PANIC").clicked() {
+                            self.search_level_panic = !self.search_level_panic;
+                            self.search();
+                        }
+                        for custom in self.custom_levels.clone() {
+                            let selected = self.search_custom_levels.contains(&custom.name);
+                            if ui.selectable_label(selected, egui::RichText::new(custom.label()).color(custom.color())).clicked() {
+                                toggle_name(&mut self.search_custom_levels, &custom.name);
+                                self.search();
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Message");
+                    ui.horizontal(|ui| {
+                        let response = ui.text_edit_singleline(&mut self.search_message);
+                        self.focus_chain.push(response.id);
+                        if response.changed() {
+                            self.search();
+                        }
+                        if clear_button(ui, &mut self.search_message) {
+                            self.search();
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Payload");
+                    ui.horizontal(|ui| {
+                        let response = ui.text_edit_singleline(&mut self.search_payload);
+                        self.focus_chain.push(response.id);
+                        if response.changed() {
+                            self.search();
+                        }
+                        if clear_button(ui, &mut self.search_payload) {
+                            self.search();
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Caller");
+                    ui.horizontal(|ui| {
+                        let response = ui.text_edit_singleline(&mut self.search_caller);
+                        self.focus_chain.push(response.id);
+                        if response.changed() {
+                            self.search();
+                        }
+                        if clear_button(ui, &mut self.search_caller) {
+                            self.search();
+                        }
+                    });
+                    ui.end_row();
+
+                    if !self.sources.is_empty() {
+                        ui.label("Source");
+                        ui.horizontal(|ui| {
+                            let response = ui.text_edit_singleline(&mut self.search_source).on_hover_text("Part of the file name the merged row was read from");
+                            self.focus_chain.push(response.id);
+                            if response.changed() {
+                                self.search();
+                            }
+                            if clear_button(ui, &mut self.search_source) {
+                                self.search();
+                            }
+                        });
+                        ui.end_row();
+                    }
+
+                    ui.label("Time");
+                    ui.horizontal(|ui| {
+                        let response = ui.text_edit_singleline(&mut self.search_time).on_hover_text("Part of the time as the Time column shows it, like 10:32");
+                        self.focus_chain.push(response.id);
+                        if response.changed() {
+                            self.search();
+                        }
+                        if clear_button(ui, &mut self.search_time) {
+                            self.search();
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Context");
+                    ui.add(egui::DragValue::new(&mut self.context_lines).clamp_range(0..=20).prefix("±").suffix(" rows"))
+                        .on_hover_text("Show the rows around the current match, including ones hidden by the filter");
+                    ui.end_row();
+
+                    ui.label("Navigation");
+                    ui.checkbox(&mut self.search_keep_scroll, "Highlight matches without scrolling to them");
+                    ui.end_row();
+                });
+        });
+    }
+
+    fn pane_ui(&mut self, ui: &mut egui::Ui, settings: &mut Settings, table_generation: &mut usize) {
+        let time_format = settings.time_format();
+        if self.time_format != time_format {
+            self.time_format = time_format;
+            if self.filter_matches.as_ref().is_some_and(|terms| !terms.time.is_empty()) {
+                self.filter();
+            }
+            if !self.search_time.is_empty() {
+                self.search();
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.menu_button("🔍", |ui| {
+                self.filter_search_ui(ui);
+            }).response.named("Filter & Search");
+
+            ui.menu_button("★", |ui| {
+                self.presets_ui(ui, settings);
+            }).response.named("Filter presets");
+
+            if ui.selectable_label(self.errors_only(), "⚠ Errors").on_hover_text("Show only ERROR and PANIC").clicked() {
+                self.toggle_errors_only();
+            }
+            if ui.small_button("⏶").named("Previous error (Alt+Up)").clicked() {
+                self.goto_error(false);
+            }
+            if ui.small_button("⏷").named("Next error (Alt+Down)").clicked() {
+                self.goto_error(true);
+            }
+
+            if ui.selectable_label(settings.utc_time, "UTC").named_toggle(settings.utc_time, "Show time in UTC").clicked() {
+                settings.utc_time = !settings.utc_time;
+            }
+
+            if ui.selectable_label(settings.time_verbatim, "ts").named_toggle(settings.time_verbatim, "Show timestamps as written in the file").clicked() {
+                settings.time_verbatim = !settings.time_verbatim;
+            }
+
+            if ui.selectable_label(settings.relative_time, "+t").named_toggle(settings.relative_time, "Show the offset from the top visible row").clicked() {
+                settings.relative_time = !settings.relative_time;
+                *table_generation += 1;
+            }
+
+            if ui.selectable_label(settings.delta_time, "Δt").named_toggle(settings.delta_time, "Show the time since the row above").clicked() {
+                settings.delta_time = !settings.delta_time;
+                *table_generation += 1;
+            }
+
+            if ui.selectable_label(settings.source_index, "#").named_toggle(settings.source_index, "Show each row's position in load order, whatever the filter and sort").clicked() {
+                settings.source_index = !settings.source_index;
+                *table_generation += 1;
+            }
+
+            let expand = egui::SelectableLabel::new(settings.expand_payloads, "¶");
+            if ui.add_enabled(self.indexed.is_none(), expand).named_toggle(settings.expand_payloads, "Show every payload pretty printed, not in indexed mode").clicked() {
+                settings.expand_payloads = !settings.expand_payloads;
+            }
+
+            if ui.selectable_label(settings.time_window, "±s").named_toggle(settings.time_window, "Highlight the rows close in time to the clicked one").clicked() {
+                settings.time_window = !settings.time_window;
+            }
+            if settings.time_window {
+                ui.add(egui::DragValue::new(&mut settings.time_window_secs).clamp_range(0.001..=3600.0).speed(0.1).prefix("±").suffix(" s"))
+                    .on_hover_text("Window around the clicked row");
+            }
+
+            if ui.add_enabled(self.indexed.is_none(), egui::SelectableLabel::new(self.follow, "👁")).named_toggle(self.follow, "Follow appended lines").clicked() {
+                self.follow = !self.follow;
+                if self.follow {
+                    self.follow_poll(settings.keep_rows());
+                    self.scroll_to_row = self.filtered_logs.len().checked_sub(1);
+                }
+                self.follow_unseen = 0;
+            }
+            let can_poll = self.indexed.is_none() && !self.follow;
+            if ui.add_enabled(can_poll, egui::SelectableLabel::new(self.auto_poll, "⟳")).named_toggle(self.auto_poll, "Poll for appended lines without scrolling").clicked() {
+                self.auto_poll = !self.auto_poll;
+            }
+
+            if ui.selectable_label(self.sort_payload, "A→Z").named_toggle(self.sort_payload, "Sort payload keys, otherwise keep the file order").clicked() {
+                self.sort_payload = !self.sort_payload;
+                self.read_file();
+            }
+
+            if ui.selectable_label(settings.payload_highlight, "{ }").named_toggle(settings.payload_highlight, "Highlight payload JSON").clicked() {
+                settings.payload_highlight = !settings.payload_highlight;
+            }
+
+            ui.menu_button("⬇", |ui| {
+                for format in [ExportFormat::Csv, ExportFormat::Ndjson] {
+                    if ui.add_enabled(self.export_job.is_none(), egui::Button::new(format!("Filtered as {}…", format.name()))).clicked() {
+                        ui.close_menu();
+                        self.export(ui.ctx(), (0..self.filtered_len()).collect(), format);
+                    }
+                }
+                ui.separator();
+                for format in [ExportFormat::Csv, ExportFormat::Ndjson] {
+                    if ui.add_enabled(!self.selection.is_empty() && self.export_job.is_none(), egui::Button::new(format!("Selection as {}…", format.name()))).clicked() {
+                        ui.close_menu();
+                        let mut rows: Vec<usize> = self.selection.iter().copied().collect();
+                        rows.sort_unstable();
+                        self.export(ui.ctx(), rows, format);
+                    }
+                }
+                ui.separator();
+                let rows = self.filtered_len();
+                let copy_hover = match rows > COPY_ALL_MAX_ROWS {
+                    true => format!("{} rows are too many for the clipboard, export them instead", rows),
+                    false => format!("{} rows", rows),
+                };
+                for (label, raw) in [("Copy all filtered as TSV", false), ("Copy all filtered raw lines", true)] {
+                    if ui.add_enabled(rows <= COPY_ALL_MAX_ROWS, egui::Button::new(label)).on_hover_text(&copy_hover).on_disabled_hover_text(&copy_hover).clicked() {
+                        ui.close_menu();
+                        self.copy_filtered(ui.ctx(), raw);
+                    }
+                }
+                ui.separator();
+                if ui.button("Summary report…").on_hover_text("Counts, time span, top callers and messages of the filtered rows").clicked() {
+                    ui.close_menu();
+                    self.export_report();
+                }
+                if ui.button("Copy summary report").clicked() {
+                    ui.close_menu();
+                    ui.ctx().copy_text(self.report());
+                }
+            }).response.named("Export");
+
+            if ui.selectable_label(self.unique_open, "1×").named_toggle(self.unique_open, "Show each distinct level, message and caller once with a count").clicked() {
+                self.unique_open = !self.unique_open;
+                self.recount_unique();
+            }
+
+            if ui.selectable_label(self.groups_open, "🔗").named_toggle(self.groups_open, "Group rows by a payload key such as a trace id").clicked() {
+                self.groups_open = !self.groups_open;
+                self.regroup();
+            }
+
+            if ui.selectable_label(self.plot_open, "📈").named_toggle(self.plot_open, "Plot a numeric payload key over time").clicked() {
+                self.plot_open = !self.plot_open;
+                self.replot();
+            }
+
+            if ui.button("↔").named("Fit columns").clicked() {
+                settings.column_widths = self.fit_columns(ui, settings);
+                *table_generation += 1;
+            }
+
+            ui.menu_button("▥", |ui| {
+                ui.label("Payload");
+                let auto = if self.payloads_empty { "Auto (hidden, all empty)" } else { "Auto" };
+                ui.radio_value(&mut self.payload_column, None, auto);
+                ui.radio_value(&mut self.payload_column, Some(true), "Show");
+                ui.radio_value(&mut self.payload_column, Some(false), "Hide");
+            }).response.named("Columns");
+
+            if ui.selectable_label(self.indexed_mode, "🗂").named_toggle(self.indexed_mode, "Indexed mode for large files, rows are parsed on demand").clicked() {
+                self.indexed_mode = !self.indexed_mode;
+                self.follow = false;
+                self.auto_poll = false;
+                self.read_file();
+            }
+
+            let format_text = match self.format_override {
+                Some(format) => format.name().to_string(),
+                None => format!("Auto ({})", self.format.name()),
+            };
+            let previous_format = self.format_override;
+            egui::ComboBox::from_id_source((&self.picked_path, "format"))
+                .selected_text(format_text)
+                .width(100.0)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.format_override, None, "Auto");
+                    for format in [LogFormat::Json, LogFormat::Logfmt, LogFormat::Text] {
+                        ui.selectable_value(&mut self.format_override, Some(format), format.name());
+                    }
+                })
+                .response
+                .on_hover_text("Log format");
+            if self.format_override != previous_format {
+                self.read_file();
+            }
+
+            if let Some(progress) = self.search_progress() {
+                ui.add(egui::ProgressBar::new(progress).desired_width(80.0).show_percentage())
+                    .on_hover_text("Searching");
+            }
+
+            ui.label("Filtered");
+            ui.monospace(self.filtered_len().to_string());
+            ui.label("from total");
+            ui.monospace(self.total_len().to_string());
+            let summary = self.filter_summary();
+            if !summary.is_empty() {
+                let text = summary.join(", ");
+                let chip = egui::Button::new(egui::RichText::new(format!("{}  ✖", truncate_chars(&text, 60).unwrap_or_else(|| text.clone()))).small())
+                    .fill(ui.visuals().selection.bg_fill.gamma_multiply(0.5))
+                    .rounding(8.0);
+                if ui.add(chip).on_hover_text(format!("Filters active: {text}\nClick to clear them")).clicked() {
+                    self.filter_reset();
+                }
+            }
+
+            ui.separator();
+            let goto = ui.add(egui::TextEdit::singleline(&mut self.goto_input).hint_text("Line").desired_width(60.0));
+            if goto.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                if let Ok(line) = self.goto_input.trim().parse() {
+                    self.goto_line(line);
+                }
+            }
+            goto.on_hover_text("Go to line");
+            if let Some(note) = &self.goto_note {
+                ui.colored_label(egui::Color32::from_rgb(240, 240, 10), note);
+            }
+            if let Some(line) = self.goto_hidden_line {
+                if ui.button("Clear filters").on_hover_text(format!("Reset the filter to show line {}", line)).clicked() {
+                    self.filter_reset();
+                    self.goto_line(line);
+                }
+            }
+        });
+
+        ui.separator();
+
+        if self.detail.is_some() {
+            if !ui.ctx().wants_keyboard_input() {
+                let (up, down) = ui.input_mut(|i| (i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp), i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)));
+                if up || down {
+                    self.step_detail(down);
+                }
+            }
+            let side = settings.detail_side;
+            let id = egui::Id::new((&self.picked_path, "detail", side));
+            let panel = if side {
+                egui::SidePanel::right(id).default_width(settings.detail_size).show_inside(ui, |ui| self.detail_ui(ui, settings))
+            } else {
+                egui::TopBottomPanel::bottom(id).default_height(settings.detail_size).show_inside(ui, |ui| self.detail_ui(ui, settings))
+            };
+            // Kept unless the panel just switched sides from its header.
+            if settings.detail_side == side {
+                let rect = panel.response.rect;
+                settings.detail_size = if side { rect.width() } else { rect.height() };
+            }
+        }
+
+        if self.groups_open {
+            egui::SidePanel::left(egui::Id::new((&self.picked_path, "groups")))
+                .default_width(280.0)
+                .show_inside(ui, |ui| {
+                    self.groups_ui(ui, settings.utc_time, settings.time_precision);
+                });
+        }
+
+        if self.plot_open {
+            egui::TopBottomPanel::bottom(egui::Id::new((&self.picked_path, "plot")))
+                .default_height(160.0)
+                .show_inside(ui, |ui| {
+                    self.plot_ui(ui, settings.utc_time, settings.time_precision);
+                });
+        }
+
+        if self.find_open {
+            egui::TopBottomPanel::bottom(egui::Id::new((&self.picked_path, "find")))
+                .resizable(false)
+                .show_inside(ui, |ui| {
+                    self.find_ui(ui);
+                });
+        }
+
+        if self.context_lines > 0 && !self.search_founds.is_empty() {
+            egui::TopBottomPanel::bottom(egui::Id::new((&self.picked_path, "context")))
+                .resizable(false)
+                .show_inside(ui, |ui| {
+                    self.context_ui(ui, settings.utc_time, settings.time_precision);
+                });
+        }
+
+        if !self.pinned.is_empty() {
+            egui::TopBottomPanel::top(egui::Id::new((&self.picked_path, "pinned")))
+                .resizable(false)
+                .show_inside(ui, |ui| {
+                    self.pinned_ui(ui, settings.utc_time, settings.time_precision);
+                });
+        }
+
+        let pane_rect = ui.available_rect_before_wrap();
+        if self.unique_open {
+            self.unique_ui(ui, settings.utc_time, settings.time_precision);
+            return;
+        }
+        self.table_ui(ui, settings, table_generation);
+
+        if self.follow && self.visible_rows.end >= self.filtered_logs.len() {
+            self.follow_unseen = 0;
+        }
+        if self.follow_unseen > 0 {
+            let size = egui::vec2(160.0, 24.0);
+            let rect = egui::Rect::from_min_size(pane_rect.right_bottom() - size - egui::vec2(24.0, 32.0), size);
+            if ui.put(rect, egui::Button::new(format!("⤓ {} new entries", self.follow_unseen))).clicked() {
+                self.scroll_to_row = self.filtered_logs.len().checked_sub(1);
+                self.follow_unseen = 0;
+            }
+        }
+    }
+
+    fn presets_ui(&mut self, ui: &mut egui::Ui, settings: &mut Settings) {
+        let mut remove = None;
+        for (index, preset) in settings.filter_presets.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let active = settings.last_preset.as_ref() == Some(&preset.name);
+                if ui.selectable_label(active, &preset.name).clicked() {
+                    self.apply_filter_state(preset.filter.clone());
+                    settings.last_preset = Some(preset.name.clone());
+                    ui.close_menu();
+                }
+                if ui.small_button("✖").named("Delete").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove {
+            let preset = settings.filter_presets.remove(index);
+            if settings.last_preset.as_ref() == Some(&preset.name) {
+                settings.last_preset = None;
+            }
+        }
+        if !settings.filter_presets.is_empty() {
+            if ui.button("No preset").on_hover_text("Reset the filter").clicked() {
+                self.filter_reset();
+                settings.last_preset = None;
+                ui.close_menu();
+            }
+            ui.separator();
+        }
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.preset_name).hint_text("Preset name").desired_width(140.0));
+            if ui.add_enabled(!self.preset_name.trim().is_empty(), egui::Button::new("Save current")).clicked() {
+                let name = std::mem::take(&mut self.preset_name).trim().to_string();
+                settings.filter_presets.retain(|preset| preset.name != name);
+                settings.filter_presets.push(FilterPreset { name: name.clone(), filter: self.filter_state() });
+                settings.last_preset = Some(name);
+            }
+        });
+    }
+
+    fn detail_ui(&mut self, ui: &mut egui::Ui, settings: &mut Settings) {
+        let side = settings.detail_side;
+        let (utc, precision) = (settings.utc_time, settings.time_precision);
+        let Some(log) = &self.detail else {
+            return;
+        };
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.strong("Detail");
+            if ui.button("✖").named("Close").clicked() {
+                close = true;
+            }
+            let (icon, name) = if side { ("⬇", "Show below the table") } else { ("➡", "Show beside the table") };
+            if ui.button(icon).named(name).clicked() {
+                settings.detail_side = !side;
+            }
+            ui.weak("↑ ↓ step through rows");
+        });
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("detail_grid").num_columns(2).spacing([40.0, 4.0]).show(ui, |ui| {
+                ui.label("Time");
+                ui.monospace(log.time_display(settings.time_verbatim, utc, precision));
+                ui.end_row();
+
+                ui.label("Level");
+                ui.colored_label(log.level_color(), log.level_label());
+                ui.end_row();
+
+                ui.label("Message");
+                ui.label(&log.message);
+                ui.end_row();
+
+                ui.label("Caller");
+                ui.monospace(&log.caller);
+                ui.end_row();
+            });
+            if !log.stacktrace.is_empty() {
+                ui.separator();
+                ui.strong("🔺 Stack trace");
+                ui.monospace(&log.stacktrace);
+            }
+            ui.separator();
+            match serde_json::from_str::<serde_json::Value>(&log.payload) {
+                Ok(payload) => json_tree_ui(ui, "payload", &payload, ui.id().with("payload")),
+                Err(_) => {
+                    ui.monospace(&log.payload);
+                }
+            }
+        });
+        if close {
+            self.detail = None;
+        }
+    }
+
+    /// Opens the row above or below the one in the detail panel and scrolls to it.
+    fn step_detail(&mut self, down: bool) {
+        let Some((row_index, _)) = self.detail.as_ref().and_then(|log| self.nearest_row(log.line)) else {
+            return;
+        };
+        let Some(next) = (if down { Some(row_index + 1) } else { row_index.checked_sub(1) }) else {
+            return;
+        };
+        if let Some(log) = self.row_log(next) {
+            self.detail = Some(log);
+            self.goto_row = Some(next);
+            self.scroll_to_row = Some(next);
+        }
+    }
+
+    /// Find bar over the message search: Enter and Shift+Enter step through the matches,
+    /// Esc closes it and clears the search.
+    fn find_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let edit = egui::TextEdit::singleline(&mut self.search_message).hint_text("Find in messages");
+            let response = ui.add(edit);
+            if std::mem::take(&mut self.find_focus) {
+                response.request_focus();
+            }
+            if response.changed() {
+                self.search();
+                self.find_jump = true;
+            }
+            if self.find_jump && !self.search_founds.is_empty() {
+                self.find_jump = false;
+                self.scroll_to_found();
+            }
+            if response.lost_focus() {
+                let (enter, shift, escape) = ui.input(|i| (i.key_pressed(egui::Key::Enter), i.modifiers.shift, i.key_pressed(egui::Key::Escape)));
+                if escape {
+                    self.find_open = false;
+                    self.search_reset();
+                    return;
+                }
+                if enter {
+                    if shift { self.search_previous(); } else { self.search_next(); }
+                    response.request_focus();
+                }
+            }
+            if ui.button("⬆").named("Previous (Shift+Enter)").clicked() {
+                self.search_previous();
+            }
+            if ui.button("⬇").named("Next (Enter)").clicked() {
+                self.search_next();
+            }
+            let count = match (self.search_founds.len(), self.search_scan.is_some()) {
+                (0, false) if self.search_message.is_empty() => "".to_string(),
+                (0, false) => "No matches".to_string(),
+                (found, scanning) => format!("{}/{}{}", (self.search_found_cursor + 1).min(found), found, if scanning { "…" } else { "" }),
+            };
+            ui.label(count);
+            if ui.button("✖").named("Close (Esc)").clicked() {
+                self.find_open = false;
+                self.search_reset();
+            }
+        });
+    }
+
+    /// Rows within `context_lines` of the current match in file order, with whether each
+    /// one passes the filter.
+    fn context_logs(&self) -> Vec<(Log, bool)> {
+        let Some(line) = self.search_founds.get(self.search_found_cursor).and_then(|&row_index| self.row_line(row_index)) else {
+            return vec![];
+        };
+        match &self.indexed {
+            Some(indexed) => {
+                let index = line - 1;
+                let end = (index + self.context_lines + 1).min(indexed.line_count());
+                (index.saturating_sub(self.context_lines)..end)
+                    .filter_map(|index| Some((indexed.log(index, self.parse_options())?, indexed.filtered.binary_search(&index).is_ok())))
+                    .collect()
+            }
+            None => {
+                let Ok(index) = self.logs.binary_search_by_key(&line, |log| log.line) else {
+                    return vec![];
+                };
+                let end = (index + self.context_lines + 1).min(self.logs.len());
+                self.logs[index.saturating_sub(self.context_lines)..end].iter().map(|log| (log.clone(), self.filter_match(log))).collect()
+            }
+        }
+    }
+
+    fn context_ui(&mut self, ui: &mut egui::Ui, utc: bool, precision: TimePrecision) {
+        let current = self.search_founds.get(self.search_found_cursor).and_then(|&row_index| self.row_line(row_index));
+        let mut goto = None;
+        ui.strong(format!("Context ±{}", self.context_lines));
+        egui::ScrollArea::vertical().max_height(240.0).auto_shrink([false, true]).show(ui, |ui| {
+            egui::Grid::new("context_grid").num_columns(4).striped(true).show(ui, |ui| {
+                for (log, visible) in self.context_logs() {
+                    let text = |text: String| {
+                        let text = egui::RichText::new(text);
+                        if Some(log.line) == current {
+                            text.strong()
+                        } else if visible {
+                            text
+                        } else {
+                            text.weak().italics()
+                        }
+                    };
+                    if ui.add(egui::Label::new(text(log.line.to_string()).monospace()).sense(egui::Sense::click())).on_hover_text("Go to line").clicked() {
+                        goto = Some(log.line);
+                    }
+                    ui.label(text(log.time_to_string(utc, precision)).monospace());
+                    ui.label(text(log.level_label().into()).color(log.level_color()));
+                    ui.label(text(log.message.clone()));
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(line) = goto {
+            self.goto_line(line);
+        }
+    }
+
+    fn pinned_ui(&mut self, ui: &mut egui::Ui, utc: bool, precision: TimePrecision) {
+        let mut unpin = None;
+        let mut goto = None;
+        egui::ScrollArea::vertical().max_height(120.0).auto_shrink([false, true]).show(ui, |ui| {
+            for log in &self.pinned {
+                ui.horizontal(|ui| {
+                    if ui.small_button("📌").named("Unpin").clicked() {
+                        unpin = Some(log.line);
+                    }
+                    ui.monospace(log.time_to_string(utc, precision));
+                    ui.colored_label(log.level_color(), log.level_label());
+                    if ui.add(egui::Label::new(&log.message).truncate(true).sense(egui::Sense::click())).on_hover_text("Go to row").clicked() {
+                        goto = Some(log.line);
+                    }
+                });
+            }
+        });
+        if let Some(line) = unpin {
+            self.toggle_pin(line, None);
+        }
+        if let Some(line) = goto {
+            self.goto_line(line);
+        }
+    }
+
+    /// Pins `log` at `line`, or unpins the row at `line` when it is already pinned.
+    fn toggle_pin(&mut self, line: usize, log: Option<&Log>) {
+        match self.pinned.iter().position(|pinned| pinned.line == line) {
+            Some(index) => {
+                self.pinned.remove(index);
+            }
+            None => {
+                let Some(log) = log else {
+                    return;
+                };
+                let at = self.pinned.partition_point(|pinned| pinned.line < line);
+                self.pinned.insert(at, log.clone());
+            }
+        }
+    }
+
+    fn table_ui(&mut self, ui: &mut egui::Ui, settings: &mut Settings, table_generation: &mut usize) {
+        let body_text_size = egui::TextStyle::Body.resolve(ui.style()).size;
+        use egui_extras::{Size, StripBuilder};
+        StripBuilder::new(ui)
+            .size(Size::remainder().at_least(0.0))
+            .size(Size::exact(body_text_size))
+            .vertical(|mut strip| {
+                strip.cell(|ui| {
+                    if settings.monospace {
+                        ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
+                    }
+                    let mut frozen_header = None;
+                    let mut frozen_rows = vec![];
+                    let mut body_clip = egui::Rect::NOTHING;
+                    let mut sort_clicked = None;
+                    let mut editor_error = None;
+                    // Header cells of the persisted columns by their index in `column_widths`,
+                    // and how far right of its cell a column divider sits.
+                    let mut header_cells = vec![];
+                    let mut divider_offset = 0.0;
+                    let show_payload = self.payload_column.unwrap_or(!self.payloads_empty);
+                    let time_format = (settings.utc_time, settings.time_precision);
+                    if settings.time_width_format != Some(time_format) {
+                        settings.time_width_format = Some(time_format);
+                        settings.column_widths[0] = time_column_width(ui, settings.utc_time, settings.time_precision);
+                    }
+                    let scroll = egui::ScrollArea::horizontal().show(ui, |ui| {
+                        // A new id when the Payload column comes and goes or the Time column was sized
+                        // again, the table keeps widths per column index.
+                        ui.push_id((self.picked_path.clone(), *table_generation, show_payload, time_format), |ui| {
+                            use egui_extras::{Column, TableBuilder};
+
+                            ui.spacing_mut().item_spacing = settings.density.item_spacing(ui.spacing().item_spacing);
+                            let text_height = settings.density.row_height(ui);
+                            divider_offset = ui.spacing().item_spacing.x * 0.5;
+                            // Indexed mode parses rows on demand, the height of every row is not known there.
+                            let expanded = settings.expand_payloads && show_payload && self.indexed.is_none();
+                            let heights = expanded.then(|| self.expanded_heights(text_height));
+
+                            let widths = &settings.column_widths;
+                            let payload_width = widths[3];
+                            let links = settings.linkify.then(link_regex);
+                            let editor = settings.editor_enabled.then_some(settings.editor_command.as_str());
+                            let fields: Vec<String> = self.text_pattern.iter().flat_map(text_fields).map(str::to_string).collect();
+                            let mut table = TableBuilder::new(ui)
+                                .striped(true)
+                                .resizable(true)
+                                .cell_layout(egui::Layout::left_to_right(if expanded { egui::Align::TOP } else { egui::Align::Center }))
+                                .column(Column::initial(widths[0]).at_least(100.0))
+                                .column(Column::initial(widths[1]).at_least(50.0));
+                            // Offsets are measured from the top row of the previous frame.
+                            let relative_from = match settings.relative_time {
+                                true => Some(self.row_log(self.visible_rows.start).and_then(|log| log.time)),
+                                false => None,
+                            };
+                            if relative_from.is_some() {
+                                table = table.column(Column::initial(90.0).at_least(60.0));
+                            }
+                            if settings.delta_time {
+                                table = table.column(Column::initial(90.0).at_least(60.0));
+                            }
+                            if settings.source_index {
+                                table = table.column(Column::initial(70.0).at_least(40.0));
+                            }
+                            let merged = !self.sources.is_empty();
+                            if merged {
+                                table = table.column(Column::initial(120.0).at_least(60.0).clip(true));
+                            }
+                            table = table.column(Column::initial(widths[2]).at_least(100.0).at_most(COLUMN_MAX_WIDTH).clip(true));
+                            if show_payload {
+                                table = table.column(Column::initial(widths[3]).at_least(100.0).at_most(COLUMN_MAX_WIDTH).clip(true));
+                            }
+                            table = table
+                                .columns(Column::initial(100.0).at_least(50.0).at_most(COLUMN_MAX_WIDTH).clip(true), fields.len())
+                                .column(Column::initial(widths[4]).at_least(100.0).at_most(COLUMN_MAX_WIDTH).clip(true))
+                                .min_scrolled_height(0.0)
+                                .max_scroll_height(2000.0);
+
+                            table = table.sense(egui::Sense::click()).stick_to_bottom(self.follow);
+
+                            if let Some(row_index) = self.scroll_to_row.take() {
+                                table = table.scroll_to_row(row_index, None);
+                            } else if let Some(row_index) = self.restore_row.take() {
+                                table = table.scroll_to_row(row_index, Some(egui::Align::TOP));
+                            }
+
+                            table
+                                .header(20.0, |mut header| {
+                                    let (time_rect, _) = header.col(|ui| {
+                                        sort_clicked = sort_clicked.or(self.sort_header_ui(ui, "Time", SortColumn::Time));
+                                    });
+                                    let (level_rect, _) = header.col(|ui| {
+                                        sort_clicked = sort_clicked.or(self.sort_header_ui(ui, "Level", SortColumn::Level));
+                                    });
+                                    frozen_header = Some((time_rect, level_rect));
+                                    header_cells.extend([(0, time_rect), (1, level_rect)]);
+                                    if relative_from.is_some() {
+                                        header.col(|ui| {
+                                            ui.strong("+Time").on_hover_text("Offset from the top visible row");
+                                        });
+                                    }
+                                    if settings.delta_time {
+                                        header.col(|ui| {
+                                            ui.strong("Δt").on_hover_text("Time since the row above");
+                                        });
+                                    }
+                                    if settings.source_index {
+                                        header.col(|ui| {
+                                            ui.strong("#").on_hover_text("Position in load order, 0 is the first entry read");
+                                        });
+                                    }
+                                    if merged {
+                                        header.col(|ui| {
+                                            ui.strong("Source");
+                                        });
+                                    }
+                                    let (message_rect, _) = header.col(|ui| {
+                                        sort_clicked = sort_clicked.or(self.sort_header_ui(ui, "Message", SortColumn::Message));
+                                    });
+                                    header_cells.push((2, message_rect));
+                                    if show_payload {
+                                        let (payload_rect, _) = header.col(|ui| {
+                                            ui.strong("Payload");
+                                        });
+                                        header_cells.push((3, payload_rect));
+                                    }
+                                    for field in &fields {
+                                        header.col(|ui| {
+                                            ui.strong(field);
+                                        });
+                                    }
+                                    let (caller_rect, _) = header.col(|ui| {
+                                        sort_clicked = sort_clicked.or(self.sort_header_ui(ui, "Caller", SortColumn::Caller));
+                                    });
+                                    header_cells.push((4, caller_rect));
+                                })
+                                .body(|body| {
+                                    // The relative and delta time, index, source and extra field columns are not persisted,
+                                    // a hidden Payload column keeps its previous width.
+                                    let mut widths = body.widths().to_vec();
+                                    widths.drain(2..2 + relative_from.is_some() as usize + settings.delta_time as usize + settings.source_index as usize + merged as usize);
+                                    if !show_payload {
+                                        widths.insert(3, payload_width);
+                                    }
+                                    settings.column_widths = widths[..4].iter().chain(widths.last()).copied().collect();
+                                    let (mut first_row, mut last_row) = (usize::MAX, 0);
+                                    let row_count = self.filtered_len();
+                                    let add_row = |mut row: egui_extras::TableRow| {
+                                        let row_index = row.index();
+                                        let Some(log) = self.row_log(row_index) else {
+                                            return;
+                                        };
+                                        first_row = first_row.min(row_index);
+                                        last_row = last_row.max(row_index + 1);
+                                        let selected = self.selection.contains(&row_index)
+                                            || self.index_at_search_found_cursor(row_index)
+                                            || self.goto_row == Some(row_index);
+                                        row.set_selected(selected);
+
+                                        let found_on_search = self.search_founds.contains(&row_index);
+                                        let arrived = self.arrived_fade(log.line);
+                                        let near_anchor = settings.time_window && self.near_time_anchor(&log, settings.time_window_secs);
+                                        let mut painter = None;
+
+                                        let ts = log.time_display(settings.time_verbatim, settings.utc_time, settings.time_precision);
+                                        let (time_rect, _) = row.col(|ui| {
+                                            body_clip = ui.clip_rect();
+                                            painter = Some(ui.painter().clone());
+                                            if found_on_search { ui.strong(&ts); } else { ui.label(&ts); }
+                                        });
+                                        let (level_rect, _) = row.col(|ui| {
+                                            if log.parse_error {
+                                                ui.colored_label(egui::Color32::from_rgb(240, 160, 10), "⚠").on_hover_text("Could not be parsed, shown as is");
+                                            } else {
+                                                ui.colored_label(log.level_color(), log.level_label());
+                                            }
+                                            if !log.stacktrace.is_empty() {
+                                                ui.label("🔺").on_hover_text("Has a stack trace, open the row for details");
+                                            }
+                                        });
+                                        frozen_rows.push(FrozenRow { time_rect, level_rect, time: ts, level: (log.level_label().to_string(), log.level_color()), index: row_index, found: found_on_search, selected });
+                                        if let Some(from) = relative_from {
+                                            row.col(|ui| {
+                                                if let (Some(from), Some(time)) = (from, log.time) {
+                                                    ui.monospace(format_offset(time - from));
+                                                }
+                                            });
+                                        }
+                                        if settings.delta_time {
+                                            // Taken from the row above in the current view, so it follows the sort.
+                                            let previous = row_index.checked_sub(1).and_then(|index| self.row_log(index)).and_then(|log| log.time);
+                                            row.col(|ui| match (previous, log.time) {
+                                                (Some(previous), Some(time)) => {
+                                                    ui.monospace(format_offset(time - previous));
+                                                }
+                                                _ => {
+                                                    ui.weak("—");
+                                                }
+                                            });
+                                        }
+                                        if settings.source_index {
+                                            row.col(|ui| {
+                                                ui.monospace(log.index.to_string());
+                                            });
+                                        }
+                                        if merged {
+                                            row.col(|ui| {
+                                                if let Some(source) = log.source.and_then(|(source, _)| self.sources.get(source)) {
+                                                    ui.label(file_name(&source.path)).on_hover_text(&source.path);
+                                                }
+                                            });
+                                        }
+                                        row.col(|ui| {
+                                            let msg = log.message.to_string();
+                                            let linked = links.as_ref().and_then(|links| linkified_ui(ui, links, &msg, found_on_search, editor, &mut editor_error));
+                                            let response = match linked {
+                                                Some(response) => response,
+                                                None => match settings.message_highlight.then(|| message_highlight(&msg, found_on_search, ui)).flatten() {
+                                                    Some(job) => ui.label(job),
+                                                    None => if found_on_search { ui.strong(msg) } else { ui.label(msg) },
+                                                },
+                                            };
+                                            clipped_hover(ui, response, &log.message, false);
+                                        });
+                                        if show_payload {
+                                            row.col(|ui| {
+                                                let (py, cut) = match truncate_chars(&log.payload, settings.payload_max_chars) {
+                                                    _ if expanded => (pretty_payload(&log.payload), false),
+                                                    Some(short) => (short, true),
+                                                    None => (log.payload.to_string(), false),
+                                                };
+                                                let linked = links.as_ref().and_then(|links| linkified_ui(ui, links, &py, found_on_search, editor, &mut editor_error));
+                                                let response = if let Some(response) = linked {
+                                                    response
+                                                } else if settings.payload_highlight {
+                                                    ui.label(json_highlight(&py, found_on_search, ui))
+                                                } else if found_on_search { ui.strong(py) } else { ui.label(py) };
+                                                clipped_hover(ui, response, &log.payload, cut);
+                                            });
+                                        }
+                                        for index in 0..fields.len() {
+                                            row.col(|ui| {
+                                                let field = log.fields.get(index).cloned().unwrap_or_default();
+                                                let response = if found_on_search { ui.strong(&field) } else { ui.label(&field) };
+                                                clipped_hover(ui, response, &field, false);
+                                            });
+                                        }
+                                        row.col(|ui| {
+                                            let ca = log.caller.to_string();
+                                            if settings.editor_enabled && caller_location(&ca).is_some() {
+                                                if ui.link(&ca).on_hover_text("Open in editor").clicked() {
+                                                    editor_error = open_in_editor(&settings.editor_command, &ca).err();
+                                                }
+                                            } else {
+                                                let response = if found_on_search { ui.strong(&ca) } else { ui.label(&ca) };
+                                                clipped_hover(ui, response, &ca, false);
+                                            }
+                                        });
+
+                                        if let (true, Some(painter)) = (near_anchor, &painter) {
+                                            painter.rect_filled(row.response().rect, 0.0, egui::Color32::from_rgba_unmultiplied(10, 160, 240, 32));
+                                        }
+                                        if let (Some(fade), Some(painter)) = (arrived, painter) {
+                                            let color = egui::Color32::from_rgba_unmultiplied(240, 240, 10, (48.0 * fade) as u8);
+                                            painter.rect_filled(row.response().rect, 0.0, color);
+                                        }
+                                        row.response().widget_info(|| {
+                                            let name = format!("row {}, {}, {}", row_index + 1, log.level_label(), log.message);
+                                            egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, selected, name)
+                                        });
+                                        let clicked = row.response().clicked();
+                                        if clicked {
+                                            self.time_anchor = log.time.map(|time| (log.line, time));
+                                        }
+                                        if settings.click_action != ClickAction::Detail {
+                                            self.toggle_row_selection(row_index, &row.response());
+                                        }
+                                        let open_detail = match settings.click_action {
+                                            ClickAction::Select => false,
+                                            ClickAction::Detail | ClickAction::Both => clicked,
+                                        };
+                                        if open_detail || (settings.double_click_detail && row.response().double_clicked()) {
+                                            self.detail = Some(log.clone());
+                                        }
+                                        row.response().context_menu(|ui| {
+                                            let pinned = self.pinned.iter().any(|pinned| pinned.line == log.line);
+                                            if ui.button(if pinned { "Unpin row" } else { "Pin row" }).clicked() {
+                                                self.toggle_pin(log.line, Some(&log));
+                                                ui.close_menu();
+                                            }
+                                            let reference = self.reference(&log);
+                                            if ui.button("Copy reference").on_hover_text(&reference).clicked() {
+                                                ui.ctx().copy_text(reference);
+                                                ui.close_menu();
+                                            }
+                                            ui.menu_button("Copy value", |ui| {
+                                                copy_value_ui(ui, "Message", &log.message);
+                                                copy_value_ui(ui, "Payload", &log.payload);
+                                                copy_value_ui(ui, "Caller", &log.caller);
+                                            });
+                                            if ui.button("Copy raw lines").clicked() {
+                                                self.copy_raw_lines(ui.ctx(), row_index);
+                                                ui.close_menu();
+                                            }
+                                            ui.menu_button("Copy timestamp", |ui| {
+                                                let formats = [
+                                                    ("RFC3339", log.time.map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, false))),
+                                                    ("Epoch millis", log.time.map(|time| time.timestamp_millis().to_string())),
+                                                    ("Original", Some(log.time_raw.clone()).filter(|raw| !raw.is_empty())),
+                                                ];
+                                                for (name, text) in formats {
+                                                    let button = egui::Button::new(name);
+                                                    let hover = text.clone().unwrap_or_default();
+                                                    if ui.add_enabled(text.is_some(), button).on_hover_text(hover).clicked() {
+                                                        ui.ctx().copy_text(text.unwrap_or_default());
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            });
+                                        });
+                                    };
+                                    match heights {
+                                        Some(heights) => body.heterogeneous_rows(heights.into_iter(), add_row),
+                                        None => body.rows(text_height, row_count, add_row),
+                                    }
+                                    self.visible_rows = first_row.min(last_row)..last_row;
+                                })
+                        });
+                    });
+
+                    if let Some(column) = sort_clicked {
+                        self.sort_by(column);
+                        settings.sort = self.sort;
+                    }
+                    if let Some(err) = editor_error {
+                        self.notice = Some(format!("Opening the editor failed: {}", err));
+                    }
+
+                    // A double click on the divider right of a header cell fits that column like "↔".
+                    let grab = ui.style().interaction.resize_grab_radius_side;
+                    let double_click = ui.input(|i| i.pointer.button_double_clicked(egui::PointerButton::Primary).then(|| i.pointer.interact_pos()).flatten());
+                    let divider = double_click.and_then(|pos| {
+                        header_cells.iter().find(|(_, rect)| rect.y_range().contains(pos.y) && (pos.x - rect.right() - divider_offset).abs() <= grab)
+                    });
+                    if let Some(&(column, _)) = divider {
+                        settings.column_widths[column] = self.fit_columns(ui, settings)[column];
+                        *table_generation += 1;
+                    }
+
+                    let shift = scroll.state.offset.x;
+                    if settings.freeze_columns && shift > 0.0 {
+                        paint_frozen_columns(ui, scroll.inner_rect, shift, frozen_header, &frozen_rows, body_clip);
+                    }
+                });
+            });
+    }
+
+    /// Height of each filtered row with its payload pretty printed, line counts are kept
+    /// per log line until the file is read again.
+    fn expanded_heights(&mut self, row_height: f32) -> Vec<f32> {
+        for log in &self.filtered_logs {
+            self.payload_lines.entry(log.line).or_insert_with(|| pretty_payload(&log.payload).lines().count());
+        }
+        self.filtered_logs.iter().map(|log| row_height * self.payload_lines[&log.line].max(1) as f32).collect()
+    }
+
+    /// Header label that sorts by `column` when clicked, sorting is not available in
+    /// indexed mode as it would parse every row.
+    fn sort_header_ui(&self, ui: &mut egui::Ui, label: &str, column: SortColumn) -> Option<SortColumn> {
+        let arrow = match self.sort {
+            Some(Sort { column: current, descending }) if current == column => if descending { " ⏷" } else { " ⏶" },
+            _ => "",
+        };
+        let text = egui::RichText::new(format!("{}{}", label, arrow)).strong();
+        if self.indexed.is_some() {
+            ui.label(text);
+            return None;
+        }
+        let response = ui.add(egui::Label::new(text).sense(egui::Sense::click())).on_hover_text("Sort");
+        response.clicked().then_some(column)
+    }
+
+    /// Writes `rows` on a worker thread, `export_poll` reports the progress.
+    fn export(&mut self, ctx: &egui::Context, rows: Vec<usize>, format: ExportFormat) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.name(), &[format.extension()])
+            .set_file_name(format!("{}.{}", self.name(), format.extension()))
+            .save_file()
+        else {
+            return;
+        };
+        let total = rows.len();
+        let logs = self.export_rows(rows);
+        let time_format = self.time_format;
+        let (sender, events) = std::sync::mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = File::create(&path).and_then(|file| {
+                let mut written = 0;
+                let logs = logs.into_iter().inspect(|_| {
+                    written += 1;
+                    if written % EXPORT_PROGRESS_ROWS == 0 {
+                        let _ = sender.send(ExportEvent::Progress(written));
+                        ctx.request_repaint();
+                    }
+                });
+                let mut writer = BufWriter::new(file);
+                write_export(&mut writer, logs, format, time_format)?;
+                writer.flush()
+            });
+            let _ = sender.send(ExportEvent::Done(result.map(|_| path).map_err(|err| err.to_string())));
+            ctx.request_repaint();
+        });
+        self.export_job = Some(ExportJob { events, written: 0, total });
+    }
+
+    /// The logs at `rows` for a worker thread. Only shared handles are taken here, the rows
+    /// are copied, or parsed in indexed mode, while the worker iterates.
+    fn export_rows(&self, rows: Vec<usize>) -> Box<dyn Iterator<Item = Log> + Send> {
+        match &self.indexed {
+            Some(indexed) => {
+                let indexed = indexed.with_lines(rows.into_iter().filter_map(|row_index| indexed.filtered.get(row_index).copied()).collect());
+                let (format, text_pattern, sort_payload, skip_comments, custom_levels) =
+                    (self.format, self.text_pattern.clone(), self.sort_payload, self.skip_comments, self.custom_levels.clone());
+                Box::new((0..indexed.filtered.len()).filter_map(move |row_index| {
+                    let options = ParseOptions { format, text_pattern: text_pattern.as_ref(), sort_payload, skip_comments, custom_levels: &custom_levels };
+                    indexed.log(indexed.filtered[row_index], options)
+                }))
+            }
+            None => {
+                let logs: Vec<std::sync::Arc<Log>> = rows.into_iter().filter_map(|row_index| self.filtered_logs.get(row_index).cloned()).collect();
+                Box::new(logs.into_iter().map(|log| Log::clone(&log)))
+            }
+        }
+    }
+
+    /// Takes the events of a running export, returns its outcome once it is done.
+    fn export_poll(&mut self) -> Option<Result<std::path::PathBuf, String>> {
+        let job = self.export_job.as_mut()?;
+        let mut done = None;
+        for event in job.events.try_iter() {
+            match event {
+                ExportEvent::Progress(written) => job.written = written,
+                ExportEvent::Done(result) => done = Some(result),
+            }
+        }
+        if done.is_some() {
+            self.export_job = None;
+        }
+        done
+    }
+
+    /// Markdown summary of the filtered rows.
+    fn report(&self) -> String {
+        let mut report = vec![];
+        let logs = (0..self.filtered_len()).filter_map(|row_index| self.row_log(row_index));
+        let _ = write_report(&mut report, &self.name(), logs);
+        String::from_utf8_lossy(&report).into_owned()
+    }
+
+    fn export_report(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Markdown", &["md"])
+            .set_file_name(format!("{}-report.md", self.name()))
+            .save_file()
+        else {
+            return;
+        };
+        let logs = (0..self.filtered_len()).filter_map(|row_index| self.row_log(row_index));
+        let written = File::create(&path).and_then(|file| write_report(BufWriter::new(file), &self.name(), logs));
+        self.notice = Some(match written {
+            Ok(()) => format!("Report saved to {}", path.display()),
+            Err(err) => format!("Saving the report failed: {}", err),
+        });
+    }
+
+    /// Copies the selected rows as their original lines, or just `row_index` when nothing is selected.
+    fn copy_raw_lines(&self, ctx: &egui::Context, row_index: usize) {
+        let mut rows: Vec<usize> = self.selection.iter().copied().collect();
+        if rows.is_empty() {
+            rows.push(row_index);
+        }
+        rows.sort_unstable();
+        let lines: Vec<String> = rows.into_iter().filter_map(|row_index| self.row_log(row_index)).map(|log| log.raw).collect();
+        ctx.copy_text(lines.join("\n"));
+    }
+
+    /// Copies every filtered row as TSV with the table columns, or as their original lines.
+    fn copy_filtered(&self, ctx: &egui::Context, raw: bool) {
+        let logs = (0..self.filtered_len()).filter_map(|row_index| self.row_log(row_index));
+        if raw {
+            let lines: Vec<String> = logs.map(|log| log.raw).collect();
+            ctx.copy_text(lines.join("\n"));
+            return;
+        }
+        let mut tsv = vec![];
+        let _ = write_export(&mut tsv, logs, ExportFormat::Tsv, self.time_format);
+        ctx.copy_text(String::from_utf8_lossy(&tsv).into_owned());
+    }
+
+    fn toggle_row_selection(&mut self, row_index: usize, row_response: &egui::Response) {
+        if row_response.clicked() {
+            let selected = !self.selection.remove(&row_index);
+            if selected {
+                self.selection.insert(row_index);
+            }
+            let info = egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, selected, self.selection_note(row_index, selected));
+            row_response.ctx.output_mut(|o| o.events.push(egui::output::OutputEvent::ValueChanged(info)));
+        }
+    }
+
+    /// What a screen reader announces when `row_index` was just selected or deselected.
+    fn selection_note(&self, row_index: usize, selected: bool) -> String {
+        let change = if selected { "selected" } else { "deselected" };
+        let rows = if self.selection.len() == 1 { "row" } else { "rows" };
+        format!("row {} {}, {} {} selected", row_index + 1, change, self.selection.len(), rows)
+    }
+
+    /// Whether `log` is another row at most `window` seconds from the clicked one.
+    fn near_time_anchor(&self, log: &Log, window: f32) -> bool {
+        let (Some((line, anchor)), Some(time)) = (self.time_anchor, log.time) else {
+            return false;
+        };
+        line != log.line && (time - anchor).num_milliseconds().unsigned_abs() as f32 <= window * 1000.0
+    }
+
+    fn row_near_time(&self, time: Option<DateTime<FixedOffset>>) -> Option<usize> {
+        if self.filtered_logs.is_empty() {
+            return None;
+        }
+        let index = self.filtered_logs.iter().position(|log| log.time >= time);
+        Some(index.unwrap_or(self.filtered_logs.len() - 1))
+    }
+
+    fn fit_columns(&self, ui: &egui::Ui, settings: &Settings) -> Vec<f32> {
+        let style = if settings.monospace { egui::TextStyle::Monospace } else { egui::TextStyle::Body };
+        let font_id = style.resolve(ui.style());
+        let measure = |text: String| {
+            ui.fonts(|f| f.layout_no_wrap(text, font_id.clone(), egui::Color32::WHITE).size().x)
+        };
+
+        let mut widths = ["Time", "Level", "Message", "Payload", "Caller"].map(|h| measure(h.to_string()));
+        let end = self.visible_rows.end.min(self.filtered_logs.len());
+        let start = self.visible_rows.start.min(end);
+        for log in &self.filtered_logs[start..end] {
+            let cells = [
+                log.time_display(settings.time_verbatim, settings.utc_time, settings.time_precision),
+                log.level_label().to_string(),
+                log.message.clone(),
+                log.payload.clone(),
+                log.caller.clone(),
+            ];
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = width.max(measure(cell));
+            }
+        }
+
+        let padding = ui.spacing().item_spacing.x * 2.0;
+        widths.iter().map(|w| (w + padding).min(COLUMN_MAX_WIDTH)).collect()
+    }
+
+    /// Re-reads the whole file and applies the current filter and search to it.
+    fn read_file(&mut self) {
+        let top_line = self.row_line(self.visible_rows.start);
+        let received = std::mem::take(&mut self.logs);
+        self.arrived.clear();
+        self.callers.clear();
+        self.payload_lines.clear();
+        self.head_lines.clear();
+        self.read_offset = 0;
+        self.indexed = None;
+        self.lines_read = 0;
+        self.lines_skipped = 0;
+        self.lines_repaired = 0;
+        self.lines_dropped = 0;
+        self.rotated_at = None;
+        let metadata = std::fs::metadata(&self.picked_path).ok();
+        self.file_size = metadata.as_ref().map(|metadata| metadata.len()).unwrap_or_default();
+        self.file_id = metadata.as_ref().and_then(file_id);
+        let started = std::time::Instant::now();
+        if is_fifo(&self.picked_path) {
+            self.read_pipe_again(received);
+            self.parse_duration = started.elapsed();
+            self.filter();
+            self.restore_top_line(top_line);
+            return;
+        }
+        self.pipe = None;
+        if !self.sources.is_empty() {
+            self.indexed_mode = false;
+            if let Ok(file) = File::open(&self.sources[0].path) {
+                let lines = BufReader::new(file).split(b'\n').map_while(Result::ok);
+                let detected = LogFormat::detect(lines.map(|line| String::from_utf8_lossy(&line).into_owned()));
+                self.format = self.format_override.unwrap_or(detected);
+            }
+            for source in &mut self.sources {
+                source.read_offset = 0;
+                source.lines_read = 0;
+            }
+            self.read_sources();
+            self.parse_duration = started.elapsed();
+            self.payloads_empty = self.logs.iter().all(|log| log.payload.is_empty());
+            self.count_callers(0);
+            self.filter();
+            self.restore_top_line(top_line);
+            return;
+        }
+        if self.indexed_mode {
+            self.sort = None;
+            self.indexed = IndexedFile::open(&self.picked_path);
+            self.parse_duration = started.elapsed();
+            if let Some(indexed) = &self.indexed {
+                self.lines_read = indexed.line_count();
+                let detected = LogFormat::detect((0..indexed.line_count()).map(|index| indexed.line(index).to_string()));
+                self.format = self.format_override.unwrap_or(detected);
+                self.head_lines = (0..indexed.line_count().min(HEAD_LINES)).map(|index| indexed.line(index).to_string()).collect();
+                self.payloads_empty = false;
+                self.filter();
+                self.restore_top_line(top_line);
+            }
+            return;
+        }
+        if let Ok(file) = File::open(&self.picked_path) {
+            let lines = BufReader::new(file).split(b'\n').map_while(Result::ok);
+            let detected = LogFormat::detect(lines.map(|line| String::from_utf8_lossy(&line).into_owned()));
+            self.format = self.format_override.unwrap_or(detected);
+        }
+        self.read_offset = self.sample_start();
+        let read = self.read_appended();
+        self.parse_duration = started.elapsed();
+        self.payloads_empty = self.logs.iter().all(|log| log.payload.is_empty());
+        self.count_callers(0);
+        if read.is_some() {
+            self.filter();
+            self.restore_top_line(top_line);
+        }
+    }
+
+    /// Where reading starts, the first full line within a tail sample.
+    fn sample_start(&self) -> u64 {
+        let Some(Sample::Tail(bytes)) = self.sample else {
+            return 0;
+        };
+        let Some(from) = self.file_size.checked_sub(bytes).filter(|&from| from > 0) else {
+            return 0;
+        };
+        let Ok(mut file) = File::open(&self.picked_path) else {
+            return 0;
+        };
+        // Start one byte early so a tail that begins right after a newline keeps its first line.
+        if file.seek(SeekFrom::Start(from - 1)).is_err() {
+            return 0;
+        }
+        let mut partial = Vec::new();
+        BufReader::new(file).read_until(b'\n', &mut partial).map_or(0, |read| from - 1 + read as u64)
+    }
+
+    /// How much of the highlight is left for a row appended at `line`, from 1 down to 0
+    /// over `ARRIVED_HIGHLIGHT`.
+    fn arrived_fade(&self, line: usize) -> Option<f32> {
+        let index = self.arrived.binary_search_by_key(&line, |&(line, _)| line).ok()?;
+        let elapsed = self.arrived[index].1.elapsed();
+        (elapsed < ARRIVED_HIGHLIGHT).then(|| 1.0 - elapsed.as_secs_f32() / ARRIVED_HIGHLIGHT.as_secs_f32())
+    }
+
+    /// Scrolls back to the row that was at the top before a reload. Follow mode keeps
+    /// the table at the bottom instead.
+    fn restore_top_line(&mut self, line: Option<usize>) {
+        if self.follow {
+            return;
+        }
+        self.restore_row = line.and_then(|line| self.nearest_row(line)).map(|(row_index, _)| row_index);
+    }
+
+    /// Opens `path` in this tab with cleared filters and search, a reload through
+    /// `read_file` keeps them.
+    fn open_file(&mut self, path: String) {
+        self.picked_path = path;
+        // Drop the previous rows first so resetting does not filter them again.
+        self.logs.clear();
+        self.indexed = None;
+        self.sources.clear();
+        self.pinned.clear();
+        self.search_reset();
+        self.filter_reset();
+        self.read_file();
+    }
+
+    /// Parses lines from `read_offset` to the end of the file, returns how many logs were added.
+    fn read_appended(&mut self) -> Option<usize> {
+        let path = self.picked_path.clone();
+        let mut offset = self.read_offset;
+        let read = self.read_lines(&path, &mut offset);
+        self.read_offset = offset;
+        read
+    }
+
+    /// Parses the lines of `path` from `offset` to its end and moves `offset` past them,
+    /// returns how many logs were added.
+    fn read_lines(&mut self, path: &str, offset: &mut u64) -> Option<usize> {
+        let mut file = File::open(path).ok()?;
+        file.seek(SeekFrom::Start(*offset)).ok()?;
+        let mut buffer = BufReader::new(file);
+        let count = self.logs.len();
+        let mut bytes = Vec::new();
+        while let Ok(read) = buffer.read_until(b'\n', &mut bytes) {
+            if read == 0 || matches!(self.sample, Some(Sample::Head(lines)) if self.lines_read >= lines) {
+                break;
+            }
+            // Invalid UTF-8 is replaced rather than dropping the line.
+            let (line, repaired) = match std::str::from_utf8(&bytes) {
+                Ok(line) => (std::borrow::Cow::Borrowed(line), false),
+                Err(_) => (String::from_utf8_lossy(&bytes), true),
+            };
+            if self.head_lines.len() < HEAD_LINES {
+                self.head_lines.push(line.trim_end().to_string());
+            }
+            let log = Log::from_line(&line, self.parse_options());
+            // A trailing line without newline may still be written, retry it on the next read
+            // unless nothing polls this file for more.
+            if line.ends_with('\n') || !self.polling() || log.as_ref().is_some_and(|log| !log.parse_error) {
+                *offset += read as u64;
+                self.push_log(log, repaired);
+            }
+            bytes.clear();
+        }
+        Some(self.logs.len() - count)
+    }
+
+    /// Reads what was appended to each file of a merged tab, a truncated one from its
+    /// start again. Rows are numbered across the files in the order they were read.
+    fn read_sources(&mut self) -> usize {
+        let mut added = 0;
+        for source in 0..self.sources.len() {
+            let Ok(metadata) = std::fs::metadata(&self.sources[source].path) else {
+                continue;
+            };
+            if metadata.len() < self.sources[source].read_offset {
+                self.sources[source].read_offset = 0;
+                self.sources[source].lines_read = 0;
+            }
+            self.sources[source].file_size = metadata.len();
+            if metadata.len() == self.sources[source].read_offset {
+                continue;
+            }
+            let path = self.sources[source].path.clone();
+            let mut offset = self.sources[source].read_offset;
+            let (from, lines_before) = (self.logs.len(), self.lines_read);
+            added += self.read_lines(&path, &mut offset).unwrap_or_default();
+            let source_lines_before = self.sources[source].lines_read;
+            for log in &mut self.logs[from..] {
+                log.source = Some((source, source_lines_before + log.line - lines_before));
+            }
+            self.sources[source].read_offset = offset;
+            self.sources[source].lines_read += self.lines_read - lines_before;
+        }
+        self.file_size = self.sources.iter().map(|source| source.file_size).sum();
+        added
+    }
+
+    /// `path:line` of a row, in the file it came from for a merged tab.
+    fn reference(&self, log: &Log) -> String {
+        match log.source.and_then(|(source, line)| Some((self.sources.get(source)?, line))) {
+            Some((source, line)) => format!("{}:{}", source.path, line),
+            None => format!("{}:{}", self.picked_path, log.line),
+        }
+    }
+
+    /// Whether `follow_poll` runs for this tab, a pipe is read whether or not it is followed.
+    fn polling(&self) -> bool {
+        self.follow || self.auto_poll || self.pipe.is_some()
+    }
+
+    /// Counts a line read and appends its log, numbered by its line.
+    fn push_log(&mut self, mut log: Option<Log>, repaired: bool) {
+        self.lines_read += 1;
+        if let Some(log) = &mut log {
+            log.line = self.lines_read;
+            // Rows dropped by `drop_oldest` still count, so indexes stay unique.
+            log.index = self.logs.len() + self.lines_dropped;
+        }
+        self.lines_repaired += repaired as usize;
+        self.lines_skipped += log.as_ref().is_some_and(|log| log.parse_error) as usize;
+        self.logs.extend(log);
+    }
+
+    /// Parses the lines that arrived through the pipe, returns how many were read.
+    fn read_pipe(&mut self) -> usize {
+        let Some(pipe) = &self.pipe else {
+            return 0;
+        };
+        let lines: Vec<Vec<u8>> = pipe.lines.try_iter().collect();
+        if self.lines_read == 0 && !lines.is_empty() {
+            let detected = LogFormat::detect(lines.iter().map(|line| String::from_utf8_lossy(line).into_owned()));
+            self.format = self.format_override.unwrap_or(detected);
+        }
+        for bytes in &lines {
+            self.file_size += bytes.len() as u64;
+            let line = String::from_utf8_lossy(bytes);
+            if self.head_lines.len() < HEAD_LINES {
+                self.head_lines.push(line.trim_end().to_string());
+            }
+            let log = Log::from_line(&line, self.parse_options());
+            let repaired = matches!(line, std::borrow::Cow::Owned(_));
+            self.push_log(log, repaired);
+        }
+        lines.len()
+    }
+
+    /// A pipe can not be read twice, a reload parses the lines received so far again.
+    /// Lines that gave no log, such as skipped comments, are gone.
+    fn read_pipe_again(&mut self, mut received: Vec<Log>) {
+        self.indexed_mode = false;
+        self.file_size = 0;
+        if self.pipe.as_ref().map(|pipe| &pipe.path) != Some(&self.picked_path) {
+            self.pipe = Some(Pipe::open(&self.picked_path));
+            self.follow = true;
+            received.clear();
+        }
+        let lines: Vec<String> = received.into_iter().map(|log| log.raw).collect();
+        if !lines.is_empty() {
+            self.format = self.format_override.unwrap_or_else(|| LogFormat::detect(lines.iter().cloned()));
+        }
+        for line in lines {
+            self.file_size += line.len() as u64 + 1;
+            if self.head_lines.len() < HEAD_LINES {
+                self.head_lines.push(line.clone());
+            }
+            let log = Log::from_line(&line, self.parse_options());
+            self.push_log(log, false);
+        }
+        self.payloads_empty = self.logs.iter().all(|log| log.payload.is_empty());
+        self.count_callers(0);
+    }
+
+    /// Reads lines appended since the last read, keeping the filter and search state.
+    /// Returns the highest level among the appended lines.
+    fn follow_poll(&mut self, keep: Option<usize>) -> Option<Level> {
+        if self.indexed.is_some() || matches!(self.sample, Some(Sample::Head(_))) {
+            return None;
+        }
+        let from = if self.pipe.is_some() || !self.sources.is_empty() {
+            let from = self.logs.len();
+            let read = if self.pipe.is_some() { self.read_pipe() } else { self.read_sources() };
+            if read == 0 {
+                return None;
+            }
+            from
+        } else {
+            self.read_file_appended()?
+        };
+        if let Some((_, reloaded)) = &mut self.rotated {
+            *reloaded = self.logs.len() - from;
+        }
+        let now = std::time::Instant::now();
+        self.payloads_empty &= self.logs[from..].iter().all(|log| log.payload.is_empty());
+        self.count_callers(from);
+        self.arrived.extend(self.logs[from..].iter().map(|log| (log.line, now)));
+        let filtered_from = self.filtered_logs.len();
+        let appended: Vec<Log> = self.logs[from..].iter().filter(|log| self.filter_match(log)).cloned().collect();
+        let sorted = self.sort.is_some();
+        self.insert_sorted(appended);
+        self.regroup();
+        self.replot();
+        self.recount_unique();
+        self.memory_estimate = self.estimate_memory();
+        // Sorted inserts already updated the matches.
+        if !sorted && self.search_active() && self.search_scan.is_none() {
+            for index in filtered_from..self.filtered_logs.len() {
+                if self.search_match(&self.filtered_logs[index]) {
+                    self.search_founds.push(index);
+                }
+            }
+        }
+
+        let added = self.filtered_logs.len() - filtered_from;
+        if self.visible_rows.end < filtered_from {
+            self.follow_unseen += added;
+        }
+        let highest = self.logs[from..].iter().map(|log| log.level).max();
+        if let Some(keep) = keep {
+            self.drop_oldest(keep);
+        }
+        highest
+    }
+
+    /// Drops the oldest rows beyond `keep`. Only runs once a tenth more has arrived, so
+    /// the rows are not moved on every poll of a busy stream.
+    fn drop_oldest(&mut self, keep: usize) {
+        if self.logs.len() <= keep + keep / 10 {
+            return;
+        }
+        let dropped = self.logs.len() - keep;
+        // Lines grow in reading order, also across the files of a merged tab.
+        let first_kept = self.logs[dropped].line;
+        self.logs.drain(..dropped);
+        self.lines_dropped += dropped;
+
+        let mut kept = 0;
+        let moved: Vec<Option<usize>> = self.filtered_logs.iter().map(|log| (log.line >= first_kept).then(|| { kept += 1; kept - 1 })).collect();
+        self.filtered_logs.retain(|log| log.line >= first_kept);
+        self.search_founds = self.search_founds.iter().filter_map(|&row_index| moved.get(row_index).copied().flatten()).collect();
+        self.search_found_cursor = self.search_found_cursor.min(self.search_founds.len().saturating_sub(1));
+        self.selection = self.selection.iter().filter_map(|&row_index| moved.get(row_index).copied().flatten()).collect();
+        self.goto_row = self.goto_row.and_then(|row_index| moved.get(row_index).copied().flatten());
+        self.payload_lines.retain(|&line, _| line >= first_kept);
+        self.count_callers(0);
+        self.regroup();
+        self.replot();
+        self.recount_unique();
+        self.memory_estimate = self.estimate_memory();
+    }
+
+    /// Reads what was appended to the file, starting over when it was truncated or replaced
+    /// by another file. Returns where the new logs start.
+    fn read_file_appended(&mut self) -> Option<usize> {
+        let metadata = std::fs::metadata(&self.picked_path).ok()?;
+        let id = file_id(&metadata);
+        if metadata.len() < self.read_offset || id != self.file_id {
+            self.rotated = Some((self.logs.len(), 0));
+            self.rotated_at = Some(std::time::Instant::now());
+            self.file_id = id;
+            self.logs.clear();
+            self.filtered_logs.clear();
+            self.payload_lines.clear();
+            self.search_founds.clear();
+            self.search_found_cursor = 0;
+            self.selection.clear();
+            self.file_size = metadata.len();
+            self.read_offset = self.sample_start();
+            self.lines_read = 0;
+            self.lines_skipped = 0;
+            self.lines_repaired = 0;
+            self.lines_dropped = 0;
+            self.arrived.clear();
+        }
+        if metadata.len() == self.read_offset {
+            return None;
+        }
+
+        let from = self.logs.len();
+        self.read_appended()?;
+        self.file_size = metadata.len();
+        Some(from)
+    }
+
+    /// Adds filtered rows, at their sorted position when a sort is active. Equal rows keep
+    /// file order so repeated timestamps stay stable. The rows of a poll are merged in one
+    /// pass, moving search matches, the match cursor and the selection once.
+    fn insert_sorted(&mut self, mut rows: Vec<Log>) {
+        let Some(sort) = self.sort else {
+            self.filtered_logs.extend(rows.into_iter().map(std::sync::Arc::new));
+            return;
+        };
+        let scanning = self.search_scan.is_some();
+        let searching = self.search_active() && !scanning;
+        let found_line = self.search_found_line();
+        rows.sort_by(|a, b| sort.compare(a, b));
+        let old = std::mem::take(&mut self.filtered_logs);
+        let mut merged = Vec::with_capacity(old.len() + rows.len());
+        // New position of each old row, and of the added rows that match the search.
+        let mut position = Vec::with_capacity(old.len());
+        let mut found = vec![];
+        let mut rows = rows.into_iter().peekable();
+        for log in old {
+            while let Some(row) = rows.next_if(|row| sort.compare(&log, row) == std::cmp::Ordering::Greater) {
+                if searching && self.search_match(&row) {
+                    found.push(merged.len());
+                }
+                merged.push(std::sync::Arc::new(row));
+            }
+            position.push(merged.len());
+            merged.push(log);
+        }
+        for row in rows {
+            if searching && self.search_match(&row) {
+                found.push(merged.len());
+            }
+            merged.push(std::sync::Arc::new(row));
+        }
+        self.filtered_logs = merged;
+        self.selection = self.selection.iter().filter_map(|&index| position.get(index).copied()).collect();
+        self.goto_row = self.goto_row.and_then(|index| position.get(index).copied());
+
+        if scanning {
+            self.search_from(found_line);
+            return;
+        }
+        let cursor = self.search_founds.get(self.search_found_cursor).and_then(|&index| position.get(index).copied());
+        let mut founds: Vec<usize> = self.search_founds.iter().filter_map(|&index| position.get(index).copied()).collect();
+        founds.extend(found);
+        founds.sort_unstable();
+        self.search_founds = founds;
+        self.search_found_cursor = cursor.and_then(|cursor| self.search_founds.binary_search(&cursor).ok()).unwrap_or(0);
+    }
+
+    /// Re-sorts the filtered rows, moving search matches, the match cursor and the
+    /// selection along with their rows.
+    fn apply_sort(&mut self, sort: Sort) {
+        let found_line = self.search_found_line();
+        let mut order: Vec<usize> = (0..self.filtered_logs.len()).collect();
+        order.sort_by(|&a, &b| sort.compare(&self.filtered_logs[a], &self.filtered_logs[b]));
+        let mut position = vec![0; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            position[old] = new;
+        }
+        let mut logs: Vec<Option<std::sync::Arc<Log>>> = std::mem::take(&mut self.filtered_logs).into_iter().map(Some).collect();
+        self.filtered_logs = order.iter().filter_map(|&old| logs[old].take()).collect();
+        self.selection = self.selection.iter().filter_map(|&index| position.get(index).copied()).collect();
+        self.goto_row = self.goto_row.and_then(|index| position.get(index).copied());
+
+        if self.search_scan.is_some() {
+            self.search_from(found_line);
+            return;
+        }
+        let cursor = self.search_founds.get(self.search_found_cursor).map(|&index| position[index]);
+        for index in &mut self.search_founds {
+            *index = position[*index];
+        }
+        self.search_founds.sort_unstable();
+        self.search_found_cursor = cursor.and_then(|cursor| self.search_founds.binary_search(&cursor).ok()).unwrap_or(0);
+    }
+
+    fn sort_by(&mut self, column: SortColumn) {
+        self.sort = match self.sort {
+            Some(Sort { column: current, descending: false }) if current == column => Some(Sort { column, descending: true }),
+            Some(Sort { column: current, descending: true }) if current == column => None,
+            _ => Some(Sort { column, descending: false }),
+        };
+        match self.sort {
+            Some(sort) => self.apply_sort(sort),
+            None => {
+                self.selection.clear();
+                self.filter();
+            }
+        }
+    }
+
+    fn compile_query(&mut self) {
+        self.filter_compiled = match self.filter_query.trim() {
+            "" => Ok(None),
+            query => Query::parse(query).map(Some),
+        };
+        self.filter_payload_compiled = match self.filter_payload.trim() {
+            "" => Ok(None),
+            _ if !self.filter_payload_path => Ok(None),
+            path => JsonPath::parse(path).map(Some),
+        };
+    }
+
+    /// Called while typing into `field`, counts the matching rows right away and leaves
+    /// rebuilding the filtered rows until typing pauses for `FILTER_DEBOUNCE`.
+    fn filter_typed(&mut self, field: &'static str) {
+        self.filter_pending = Some(std::time::Instant::now());
+        self.compile_query();
+        // Counting in indexed mode would parse every line on each key press.
+        self.filter_preview = match self.indexed {
+            Some(_) => None,
+            None => Some((field, self.logs.iter().filter(|row| self.filter_match(row)).count())),
+        };
+    }
+
+    fn filter_preview_ui(&self, ui: &mut egui::Ui, field: &str) {
+        if let Some((_, count)) = self.filter_preview.filter(|(preview, _)| *preview == field) {
+            ui.weak(format!("({})", count)).on_hover_text("Matching rows");
+        }
+    }
+
+    fn filter(&mut self) {
+        let state = self.filter_state();
+        if state != self.filter_applied {
+            self.filter_undo_stack.push(std::mem::replace(&mut self.filter_applied, state));
+            if self.filter_undo_stack.len() > FILTER_HISTORY_LIMIT {
+                self.filter_undo_stack.remove(0);
+            }
+            self.filter_redo_stack.clear();
+        }
+        self.filter_pending = None;
+        self.filter_preview = None;
+        self.compile_query();
+        // The selection holds view positions, it follows its rows by their line.
+        let selected: std::collections::HashSet<usize> = self.selection.iter().filter_map(|&row_index| self.row_line(row_index)).collect();
+        let found_line = self.search_found_line();
+
+        if let Some(indexed) = &self.indexed {
+            let filtered = (0..indexed.line_count())
+                .filter(|&line| indexed.log(line, self.parse_options()).is_some_and(|row| self.filter_match(&row)))
+                .collect();
+            if let Some(indexed) = &mut self.indexed {
+                indexed.filtered = filtered;
+            }
+        } else {
+            self.filtered_logs = self.logs.iter()
+                .filter(|row| self.filter_match(row))
+                .map(|row| std::sync::Arc::new(row.clone()))
+                .collect::<Vec<_>>();
+            if let Some(sort) = self.sort {
+                self.filtered_logs.sort_by(|a, b| sort.compare(a, b));
+            }
+        }
+        self.selection = match selected.is_empty() {
+            true => Default::default(),
+            false => (0..self.filtered_len()).filter(|&row_index| self.row_line(row_index).is_some_and(|line| selected.contains(&line))).collect(),
+        };
+        self.memory_estimate = self.estimate_memory();
+        self.goto_row = None;
+        self.goto_note = None;
+        self.goto_hidden_line = None;
+        self.regroup();
+        self.replot();
+        self.recount_unique();
+        self.search_from(found_line);
+    }
+
+    /// Clusters the filtered rows by the value at `group_key` in their payload, in order
+    /// of first appearance. Rows without the key are left out.
+    fn regroup(&mut self) {
+        self.groups.clear();
+        if !self.groups_open || self.group_key.trim().is_empty() {
+            return;
+        }
+        let path: Vec<String> = self.group_key.trim().split('.').map(str::to_string).collect();
+        let mut index = std::collections::HashMap::new();
+        for row_index in 0..self.filtered_len() {
+            let Some(log) = self.row_log(row_index) else {
+                continue;
+            };
+            let Some(id) = payload_value(&log.payload, &path) else {
+                continue;
+            };
+            let group = *index.entry(id.clone()).or_insert_with(|| {
+                self.groups.push(LogGroup { id, lines: vec![] });
+                self.groups.len() - 1
+            });
+            self.groups[group].lines.push(log.line);
+        }
+    }
+
+    /// Collects the numeric values at `plot_key` in the payloads of the filtered rows,
+    /// rows without a time or a number there are left out.
+    fn replot(&mut self) {
+        self.plot_points.clear();
+        if !self.plot_open || self.plot_key.trim().is_empty() {
+            return;
+        }
+        let path: Vec<String> = self.plot_key.trim().split('.').map(str::to_string).collect();
+        for row_index in 0..self.filtered_len() {
+            let Some(log) = self.row_log(row_index) else {
+                continue;
+            };
+            let Some(time) = log.time else {
+                continue;
+            };
+            let Some(value) = payload_value(&log.payload, &path).and_then(|value| value.parse::<f64>().ok()).filter(|value| value.is_finite()) else {
+                continue;
+            };
+            self.plot_points.push([time.timestamp_millis() as f64 / 1000.0, value]);
+        }
+        // The table may be sorted by another column.
+        self.plot_points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+    }
+
+    fn plot_ui(&mut self, ui: &mut egui::Ui, utc: bool, precision: TimePrecision) {
+        ui.horizontal(|ui| {
+            ui.label("Plot");
+            let edit = egui::TextEdit::singleline(&mut self.plot_key).hint_text("queue_depth").desired_width(120.0);
+            if ui.add(edit).on_hover_text("Payload key with numeric values, nested keys are separated by dots").changed() {
+                self.replot();
+            }
+            ui.weak(format!("{} points", self.plot_points.len()));
+        });
+        let time = move |seconds: f64| format_time(Utc.timestamp_millis_opt((seconds * 1000.0).round() as i64).single().map(|time| time.fixed_offset()), utc, precision);
+        egui_plot::Plot::new((&self.picked_path, "plot"))
+            .allow_scroll(false)
+            .x_axis_formatter(move |mark, _, _| time(mark.value))
+            .label_formatter(move |_, point| format!("{}\n{}", time(point.x), point.y))
+            .show(ui, |plot_ui| {
+                plot_ui.line(egui_plot::Line::new(egui_plot::PlotPoints::from(self.plot_points.clone())));
+            });
+    }
+
+    /// Counts the distinct level, message and caller combinations of the filtered rows,
+    /// most frequent first.
+    fn recount_unique(&mut self) {
+        self.uniques.clear();
+        if !self.unique_open {
+            return;
+        }
+        let mut index = std::collections::HashMap::new();
+        for row_index in 0..self.filtered_len() {
+            let Some(log) = self.row_log(row_index) else {
+                continue;
+            };
+            let key = (log.level, log.custom_level.as_ref().map(|custom| custom.name.clone()), log.message.clone(), log.caller.clone());
+            let unique = *index.entry(key).or_insert_with(|| {
+                self.uniques.push(UniqueLog { level: log.level, custom_level: log.custom_level.clone(), message: log.message.clone(), caller: log.caller.clone(), count: 0, first: log.time, last: log.time });
+                self.uniques.len() - 1
+            });
+            let unique = &mut self.uniques[unique];
+            unique.count += 1;
+            unique.first = unique.first.min(log.time).or(log.time);
+            unique.last = unique.last.max(log.time);
+        }
+        self.uniques.sort_by_key(|unique| std::cmp::Reverse(unique.count));
+    }
+
+    fn unique_ui(&mut self, ui: &mut egui::Ui, utc: bool, precision: TimePrecision) {
+        use egui_extras::{Column, TableBuilder};
+
+        let mut filter_to = None;
+        ui.weak(format!("{} distinct of {} rows, click one to filter to its rows", self.uniques.len(), self.filtered_len()));
+        ui.push_id((&self.picked_path, "unique"), |ui| {
+            TableBuilder::new(ui)
+                .striped(true)
+                .resizable(true)
+                .sense(egui::Sense::click())
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                .column(Column::initial(60.0).at_least(40.0))
+                .column(Column::initial(50.0).at_least(50.0))
+                .column(Column::initial(400.0).at_least(100.0).clip(true))
+                .column(Column::initial(200.0).at_least(100.0).clip(true))
+                .column(Column::initial(160.0).at_least(100.0))
+                .column(Column::remainder().at_least(100.0))
+                .min_scrolled_height(0.0)
+                .header(20.0, |mut header| {
+                    for title in ["Count", "Level", "Message", "Caller", "First seen", "Last seen"] {
+                        header.col(|ui| {
+                            ui.strong(title);
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(18.0, self.uniques.len(), |mut row| {
+                        let unique = &self.uniques[row.index()];
+                        row.col(|ui| {
+                            ui.monospace(unique.count.to_string());
+                        });
+                        row.col(|ui| {
+                            match &unique.custom_level {
+                                Some(custom) => ui.colored_label(custom.color(), custom.label()),
+                                None => ui.colored_label(unique.level.color(), unique.level.to_string()),
+                            };
+                        });
+                        row.col(|ui| {
+                            let response = ui.label(&unique.message);
+                            clipped_hover(ui, response, &unique.message, false);
+                        });
+                        row.col(|ui| {
+                            ui.label(&unique.caller);
+                        });
+                        row.col(|ui| {
+                            ui.monospace(format_time(unique.first, utc, precision));
+                        });
+                        row.col(|ui| {
+                            ui.monospace(format_time(unique.last, utc, precision));
+                        });
+                        if row.response().clicked() {
+                            filter_to = Some(row.index());
+                        }
+                    });
+                });
+        });
+        if let Some(index) = filter_to {
+            let unique = &self.uniques[index];
+            let quote = |text: &str| format!("\"{}\"", text.replace('"', "\\\""));
+            let mut query = format!("message={} caller={}", quote(&unique.message), quote(&unique.caller));
+            // The query knows only the standard levels, a custom one is left to the message and caller.
+            if unique.level != Level::Unknown && unique.custom_level.is_none() {
+                query = format!("level={} {}", unique.level.to_string(), query);
+            }
+            self.filter_query = query;
+            self.unique_open = false;
+            self.filter();
+        }
+    }
+
+    /// The parsed row at file `line`, whether or not it passes the filter.
+    fn log_at_line(&self, line: usize) -> Option<Log> {
+        match &self.indexed {
+            Some(indexed) => indexed.log(line.checked_sub(1)?, self.parse_options()),
+            None => self.logs.binary_search_by_key(&line, |log| log.line).ok().map(|index| self.logs[index].clone()),
+        }
+    }
+
+    fn groups_ui(&mut self, ui: &mut egui::Ui, utc: bool, precision: TimePrecision) {
+        ui.horizontal(|ui| {
+            ui.label("Group by");
+            let edit = egui::TextEdit::singleline(&mut self.group_key).hint_text("trace_id").desired_width(120.0);
+            if ui.add(edit).on_hover_text("Payload key, nested keys are separated by dots").changed() {
+                self.regroup();
+            }
+        });
+        ui.weak(format!("{} groups", self.groups.len()));
+        ui.separator();
+        let mut goto = None;
+        let mut filter_to = None;
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            for group in &self.groups {
+                egui::CollapsingHeader::new(format!("{} ({})", group.id, group.lines.len()))
+                    .id_source((&self.picked_path, "group", &group.id))
+                    .show(ui, |ui| {
+                        if ui.small_button("Filter to this id").clicked() {
+                            filter_to = Some(group.id.clone());
+                        }
+                        for &line in group.lines.iter().take(GROUP_ROWS_SHOWN) {
+                            let Some(log) = self.log_at_line(line) else {
+                                continue;
+                            };
+                            let text = format!("{} {} {}", log.time_to_string(utc, precision), log.level_label(), log.message);
+                            if ui.add(egui::Label::new(text).truncate(true).sense(egui::Sense::click())).on_hover_text("Go to row").clicked() {
+                                goto = Some(line);
+                            }
+                        }
+                        if group.lines.len() > GROUP_ROWS_SHOWN {
+                            ui.weak(format!("{} more", group.lines.len() - GROUP_ROWS_SHOWN));
+                        }
+                    });
+            }
+        });
+        if let Some(line) = goto {
+            self.goto_line(line);
+        }
+        if let Some(id) = filter_to {
+            self.filter_query = format!("payload.{}=\"{}\"", self.group_key.trim(), id.replace('"', "\\\""));
+            self.filter();
+        }
+    }
+
+    /// Adds the callers of `logs[from..]` to `callers`, from 0 counts them anew.
+    fn count_callers(&mut self, from: usize) {
+        let mut counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        if from > 0 {
+            counts.extend(self.callers.iter().map(|(caller, count)| (caller.as_str(), *count)));
+        }
+        for log in self.logs[from.min(self.logs.len())..].iter().filter(|log| !log.caller.is_empty()) {
+            *counts.entry(&log.caller).or_default() += 1;
+        }
+        let mut callers: Vec<(String, usize)> = counts.into_iter().map(|(caller, count)| (caller.to_string(), count)).collect();
+        callers.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        self.callers = callers;
+    }
+
+    /// Rough heap usage of the parsed rows, the memory map itself is left to the OS.
+    fn estimate_memory(&self) -> usize {
+        let logs: usize = self.logs.iter().chain(self.filtered_logs.iter().map(|log| &**log)).map(Log::heap_size).sum();
+        let indexed = self.indexed.as_ref().map_or(0, |indexed| (indexed.line_starts.capacity() + indexed.filtered.capacity()) * std::mem::size_of::<usize>());
+        logs + indexed
+    }
+
+    fn status_ui(&self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Size");
+            ui.monospace(format_bytes(self.file_size as usize));
+            ui.separator();
+            ui.label("Lines");
+            ui.monospace(self.lines_read.to_string());
+            if !self.selection.is_empty() {
+                ui.separator();
+                let response = ui.label(format!("{} selected", self.selection.len()));
+                ui.ctx().accesskit_node_builder(response.id, |builder| builder.set_live(egui::accesskit::Live::Polite));
+            }
+            if let Some(rotated_at) = self.rotated_at {
+                ui.weak("reloaded (rotated)").on_hover_text(format!("The file was truncated or replaced {:.0?} ago and read again from the top", rotated_at.elapsed()));
+            }
+            if self.pipe.is_some() {
+                ui.label("(pipe)").on_hover_text("Reading from a named pipe, lines appear as they are written");
+            }
+            match self.sample {
+                Some(Sample::Tail(bytes)) => {
+                    ui.label(format!("(last {})", format_bytes(bytes as usize))).on_hover_text("Only the end of the file is loaded, line numbers count from there");
+                }
+                Some(Sample::Head(lines)) => {
+                    ui.label(format!("(first {lines})")).on_hover_text("Only the start of the file is loaded");
+                }
+                None => {}
+            }
+            if self.lines_skipped > 0 {
+                ui.label("unparsed");
+                ui.monospace(self.lines_skipped.to_string()).on_hover_text("Lines that could not be parsed, shown with ⚠");
+            }
+            if self.lines_repaired > 0 {
+                ui.label("repaired");
+                ui.monospace(self.lines_repaired.to_string()).on_hover_text("Lines with invalid UTF-8 that were decoded lossily");
+            }
+            if self.lines_dropped > 0 {
+                ui.label("dropped");
+                ui.monospace(self.lines_dropped.to_string()).on_hover_text("Oldest rows dropped while following to stay within the row limit");
+            }
+            if !self.callers.is_empty() {
+                ui.separator();
+                ui.label("Callers");
+                ui.monospace(self.callers.len().to_string());
+            }
+            ui.separator();
+            ui.label("Parsed in");
+            ui.monospace(format!("{:.2?}", self.parse_duration));
+            if let Some(job) = &self.export_job {
+                ui.separator();
+                let progress = job.written as f32 / job.total.max(1) as f32;
+                ui.add(egui::ProgressBar::new(progress).desired_width(160.0).text(format!("Exporting {} of {}", job.written, job.total)));
+            }
+            ui.separator();
+            ui.label("Memory");
+            ui.monospace(format!("≈ {}", format_bytes(self.memory_estimate)));
+        });
+    }
+
+    fn parse_options(&self) -> ParseOptions<'_> {
+        ParseOptions {
+            format: self.format,
+            text_pattern: self.text_pattern.as_ref(),
+            sort_payload: self.sort_payload,
+            skip_comments: self.skip_comments,
+            custom_levels: &self.custom_levels,
+        }
+    }
+
+    fn row_line(&self, row_index: usize) -> Option<usize> {
+        match &self.indexed {
+            Some(indexed) => indexed.filtered.get(row_index).map(|index| index + 1),
+            None => self.filtered_logs.get(row_index).map(|log| log.line),
+        }
+    }
+
+    /// Scrolls to file `line`. A line hidden by the filter, or one that is not a log
+    /// entry, lands on the nearest visible row with a note saying why.
+    fn goto_line(&mut self, line: usize) {
+        self.goto_note = None;
+        self.goto_hidden_line = None;
+        let Some((row_index, row_line)) = self.nearest_row(line) else {
+            self.goto_note = Some("No visible rows".to_string());
+            return;
+        };
+        self.goto_row = Some(row_index);
+        self.scroll_to_row = Some(row_index);
+        if row_line == line {
+            return;
+        }
+        let is_entry = match &self.indexed {
+            Some(indexed) => line >= 1 && line <= indexed.line_count() && indexed.log(line - 1, self.parse_options()).is_some(),
+            None => self.logs.binary_search_by_key(&line, |log| log.line).is_ok(),
+        };
+        if is_entry {
+            self.goto_note = Some(format!("Line {} is filtered out, showing line {}", line, row_line));
+            self.goto_hidden_line = Some(line);
+        } else {
+            self.goto_note = Some(format!("Line {} is not a log entry, showing line {}", line, row_line));
+        }
+    }
+
+    /// Moves the highlighted row to the next or previous ERROR or PANIC row, starting from
+    /// the highlighted row or the top of the view.
+    fn goto_error(&mut self, forward: bool) {
+        let from = self.goto_row.unwrap_or(self.visible_rows.start);
+        let is_error = |row_index: usize| match &self.indexed {
+            Some(_) => self.row_log(row_index).is_some_and(|log| log.level >= Level::Error),
+            None => self.filtered_logs[row_index].level >= Level::Error,
+        };
+        let found = if forward {
+            let start = if self.goto_row.is_some() { from + 1 } else { from };
+            (start..self.filtered_len()).find(|&row_index| is_error(row_index))
+        } else {
+            (0..from.min(self.filtered_len())).rev().find(|&row_index| is_error(row_index))
+        };
+        if let Some(row_index) = found {
+            self.goto_row = Some(row_index);
+            self.scroll_to_row = Some(row_index);
+            self.goto_note = None;
+        }
+    }
+
+    /// The visible row closest to file `line`, with its line.
+    fn nearest_row(&self, line: usize) -> Option<(usize, usize)> {
+        (0..self.filtered_len())
+            .filter_map(|row_index| Some((row_index, self.row_line(row_index)?)))
+            .min_by_key(|&(_, row_line)| row_line.abs_diff(line))
+    }
+
+    fn filtered_len(&self) -> usize {
+        match &self.indexed {
+            Some(indexed) => indexed.filtered.len(),
+            None => self.filtered_logs.len(),
+        }
+    }
+
+    fn total_len(&self) -> usize {
+        match &self.indexed {
+            Some(indexed) => indexed.line_count(),
+            None => self.logs.len(),
+        }
+    }
+
+    /// The log shown at `row_index` of the filtered view, parsed on demand in indexed mode.
+    fn row_log(&self, row_index: usize) -> Option<Log> {
+        match &self.indexed {
+            Some(indexed) => indexed.log(*indexed.filtered.get(row_index)?, self.parse_options()),
+            None => self.filtered_logs.get(row_index).map(|log| Log::clone(log)),
+        }
+    }
+
+    fn filter_match(&self, row: &Log) -> bool {
+        let level = match &row.custom_level {
+            Some(custom) => !self.filter_hidden_levels.contains(&custom.name),
+            None => {
+                let mut level = row.level == Level::Unknown;
+                level |= row.level == Level::Debug && self.filter_level_debug;
+                level |= row.level == Level::Info && self.filter_level_info;
+                level |= row.level == Level::Warning && self.filter_level_warning;
+                level |= row.level == Level::Error && self.filter_level_error;
+                level |= row.level == Level::Panic && self.filter_level_panic;
+                level
+            }
+        };
+        let message = text_match(&row.message, &self.filter_message, self.fold_accents, self.whole_word);
+        let payload = match &self.filter_payload_compiled {
+            _ if !self.filter_payload_path => text_match(&row.payload, &self.filter_payload, self.fold_accents, self.whole_word),
+            Ok(Some(path)) => path.matches(&row.payload),
+            _ => true,
+        };
+        let caller = text_match(&row.caller, &self.filter_caller, self.fold_accents, self.whole_word);
+        let source = text_match(self.source_name(row), &self.filter_source, self.fold_accents, self.whole_word);
+        let query = match &self.filter_compiled {
+            Ok(Some(query)) => query.matches(row, self.fold_accents),
+            _ => true,
+        };
+        let matches = self.filter_matches.as_ref().map_or(true, |terms| terms.matches(row, self.source_name(row), self.time_format, self.fold_accents, self.whole_word));
+        let stacktrace = !self.filter_stacktrace || !row.stacktrace.is_empty();
+        let parse_error = match self.filter_parse_errors {
+            ParseErrorFilter::Show => true,
+            ParseErrorFilter::Hide => !row.parse_error,
+            ParseErrorFilter::Only => row.parse_error,
+        };
+        if !self.filter_any {
+            return level && message && payload && caller && source && query && matches && stacktrace && parse_error;
+        }
+        // Only the criteria that are set take part, rows without a level match none of the levels.
+        let criteria = [
+            (self.filter_levels() != [true; 5] || !self.filter_hidden_levels.is_empty(), level && row.level != Level::Unknown),
+            (!self.filter_message.is_empty(), message),
+            (!self.filter_payload.is_empty(), payload),
+            (!self.filter_caller.is_empty(), caller),
+            (!self.filter_source.is_empty(), source),
+            (matches!(self.filter_compiled, Ok(Some(_))), query),
+            (self.filter_matches.is_some(), matches),
+            (self.filter_stacktrace, stacktrace),
+            (self.filter_parse_errors != ParseErrorFilter::Show, parse_error),
+        ];
+        let mut active = criteria.iter().filter(|(set, _)| *set).peekable();
+        active.peek().is_none() || active.any(|&(_, matched)| matched)
+    }
+
+    fn filter_state(&self) -> FilterState {
+        FilterState {
+            level_debug: self.filter_level_debug,
+            level_info: self.filter_level_info,
+            level_warning: self.filter_level_warning,
+            level_error: self.filter_level_error,
+            level_panic: self.filter_level_panic,
+            hidden_levels: self.filter_hidden_levels.clone(),
+            message: self.filter_message.clone(),
+            payload: self.filter_payload.clone(),
+            payload_path: self.filter_payload_path,
+            caller: self.filter_caller.clone(),
+            source: self.filter_source.clone(),
+            query: self.filter_query.clone(),
+            matches: self.filter_matches.clone(),
+            any: self.filter_any,
+            stacktrace: self.filter_stacktrace,
+            parse_errors: self.filter_parse_errors,
+        }
+    }
+
+    fn apply_filter_state(&mut self, state: FilterState) {
+        self.filter_level_debug = state.level_debug;
+        self.filter_level_info = state.level_info;
+        self.filter_level_warning = state.level_warning;
+        self.filter_level_error = state.level_error;
+        self.filter_level_panic = state.level_panic;
+        self.filter_hidden_levels = state.hidden_levels;
+        self.filter_message = state.message;
+        self.filter_payload = state.payload;
+        self.filter_payload_path = state.payload_path;
+        self.filter_caller = state.caller;
+        self.filter_source = state.source;
+        self.filter_query = state.query;
+        self.filter_matches = state.matches;
+        self.filter_any = state.any;
+        self.filter_stacktrace = state.stacktrace;
+        self.filter_parse_errors = state.parse_errors;
+        self.filter();
+    }
+
+    /// One short term per filter criterion that is set, `level≥WARN`, `caller='db'` and so on.
+    fn filter_summary(&self) -> Vec<String> {
+        let quoted = |text: &str| format!("'{}'", truncate_chars(text, 20).unwrap_or_else(|| text.to_string()));
+        let mut summary = vec![];
+        let levels = self.filter_levels();
+        if levels != [true; 5] {
+            let names = [Level::Debug, Level::Info, Level::Warning, Level::Error, Level::Panic];
+            let lowest = levels.iter().position(|&shown| shown);
+            match lowest {
+                Some(lowest) if levels[lowest..].iter().all(|&shown| shown) => summary.push(format!("level≥{}", names[lowest].to_string())),
+                _ => {
+                    let shown: Vec<&str> = names.iter().zip(levels).filter(|(_, shown)| *shown).map(|(level, _)| level.to_string()).collect();
+                    summary.push(format!("level={}", if shown.is_empty() { "none".to_string() } else { shown.join("|") }));
+                }
+            }
+        }
+        for name in &self.filter_hidden_levels {
+            summary.push(format!("level≠{name}"));
+        }
+        if !self.filter_message.is_empty() {
+            summary.push(format!("msg={}", quoted(&self.filter_message)));
+        }
+        if !self.filter_payload.is_empty() {
+            let name = if self.filter_payload_path { "payload $" } else { "payload=" };
+            summary.push(format!("{name}{}", quoted(&self.filter_payload)));
+        }
+        if !self.filter_caller.is_empty() {
+            summary.push(format!("caller={}", quoted(&self.filter_caller)));
+        }
+        if !self.filter_source.is_empty() {
+            summary.push(format!("source={}", quoted(&self.filter_source)));
+        }
+        if !self.filter_query.trim().is_empty() {
+            summary.push(format!("query {}", quoted(self.filter_query.trim())));
+        }
+        if self.filter_matches.is_some() {
+            summary.push("search matches".to_string());
+        }
+        if self.filter_stacktrace {
+            summary.push("stack trace".to_string());
+        }
+        match self.filter_parse_errors {
+            ParseErrorFilter::Show => {}
+            ParseErrorFilter::Hide => summary.push("no unparsed".to_string()),
+            ParseErrorFilter::Only => summary.push("only unparsed".to_string()),
+        }
+        if self.filter_any && summary.len() > 1 {
+            summary.insert(0, "any of".to_string());
+        }
+        summary
+    }
+
+    fn errors_only(&self) -> bool {
+        self.filter_levels() == [false, false, false, true, true]
+    }
+
+    fn filter_levels(&self) -> [bool; 5] {
+        [self.filter_level_debug, self.filter_level_info, self.filter_level_warning, self.filter_level_error, self.filter_level_panic]
+    }
+
+    /// Narrows the level filter to ERROR and PANIC, a second toggle restores the levels
+    /// set before, or all of them.
+    fn toggle_errors_only(&mut self) {
+        let levels = if self.errors_only() {
+            self.levels_before_errors_only.take().unwrap_or([true; 5])
+        } else {
+            self.levels_before_errors_only = Some(self.filter_levels());
+            [false, false, false, true, true]
+        };
+        [self.filter_level_debug, self.filter_level_info, self.filter_level_warning, self.filter_level_error, self.filter_level_panic] = levels;
+        self.filter();
+    }
+
+    fn filter_undo(&mut self) {
+        if let Some(state) = self.filter_undo_stack.pop() {
+            self.filter_redo_stack.push(std::mem::replace(&mut self.filter_applied, state.clone()));
+            self.apply_filter_state(state);
+        }
+    }
+
+    fn filter_redo(&mut self) {
+        if let Some(state) = self.filter_redo_stack.pop() {
+            self.filter_undo_stack.push(std::mem::replace(&mut self.filter_applied, state.clone()));
+            self.apply_filter_state(state);
+        }
+    }
+
+    fn filter_reset(&mut self) {
+        self.filter_level_debug = true;
+        self.filter_level_info = true;
+        self.filter_level_warning = true;
+        self.filter_level_error = true;
+        self.filter_level_panic = true;
+        self.filter_hidden_levels.clear();
+        self.filter_message = "".to_string();
+        self.filter_payload = "".to_string();
+        self.filter_caller = "".to_string();
+        self.filter_source = "".to_string();
+        self.filter_query = "".to_string();
+        self.filter_payload_path = false;
+        self.filter_matches = None;
+        self.filter_any = false;
+        self.filter_stacktrace = false;
+        self.filter_parse_errors = ParseErrorFilter::Show;
+        self.filter();
+    }
+
+    fn search(&mut self) {
+        let line = self.search_found_line();
+        self.search_from(line);
+    }
+
+    /// File line of the match at the cursor, or of the one a running search looks for.
+    fn search_found_line(&self) -> Option<usize> {
+        self.search_cursor_line.or_else(|| self.search_founds.get(self.search_found_cursor).and_then(|&row_index| self.row_line(row_index)))
+    }
+
+    /// Starts the search over. The cursor lands on the match at file `line` again when it
+    /// still matches, otherwise it keeps its index clamped into the new matches.
+    fn search_from(&mut self, line: Option<usize>) {
+        if !self.search_active() {
+            self.search_reset();
+            return;
+        }
+
+        self.search_founds.clear();
+        self.search_scan = Some(0);
+        self.search_cursor_line = line;
+    }
+
+    /// Keeps the match cursor inside `search_founds`, 0 when there are no matches.
+    fn clamp_search_cursor(&mut self) {
+        self.search_found_cursor = self.search_found_cursor.min(self.search_founds.len().saturating_sub(1));
+    }
+
+    /// Continues the running search for at most `budget`, returns whether rows are left to scan.
+    fn search_step(&mut self, budget: std::time::Duration) -> bool {
+        let Some(mut index) = self.search_scan else {
+            return false;
+        };
+        let started = std::time::Instant::now();
+        let len = self.filtered_len();
+        while index < len {
+            let found = match &self.indexed {
+                Some(_) => self.row_log(index).is_some_and(|row| self.search_match(&row)),
+                None => self.search_match(&self.filtered_logs[index]),
+            };
+            if found {
+                if self.search_cursor_line.is_some() && self.search_cursor_line == self.row_line(index) {
+                    self.search_found_cursor = self.search_founds.len();
+                    self.search_cursor_line = None;
+                }
+                self.search_founds.push(index);
+            }
+            index += 1;
+            if index % 256 == 0 && started.elapsed() >= budget {
+                break;
+            }
+        }
+        self.search_scan = (index < len).then_some(index);
+        // The old index is kept until the scan is done, the row may still come.
+        if self.search_scan.is_none() {
+            self.search_cursor_line = None;
+        }
+        if self.search_cursor_line.is_none() {
+            self.clamp_search_cursor();
+        }
+        self.search_scan.is_some()
+    }
+
+    fn search_progress(&self) -> Option<f32> {
+        self.search_scan.map(|index| index as f32 / self.filtered_len().max(1) as f32)
+    }
+
+    fn search_active(&self) -> bool {
+        self.search_level_debug
+            || self.search_level_info
+            || self.search_level_warning
+            || self.search_level_error
+            || self.search_level_panic
+            || !self.search_custom_levels.is_empty()
+            || !self.search_message.is_empty()
+            || !self.search_payload.is_empty()
+            || !self.search_caller.is_empty()
+            || !self.search_source.is_empty()
+            || !self.search_time.is_empty()
+    }
+
+    fn search_match(&self, row: &Log) -> bool {
+        level_match(row, self.search_levels(), &self.search_custom_levels)
+            && terms_match(row, &self.search_message, &self.search_payload, &self.search_caller, self.fold_accents, self.whole_word)
+            && text_match(self.source_name(row), &self.search_source, self.fold_accents, self.whole_word)
+            && time_match(row, &self.search_time, self.time_format, self.fold_accents, self.whole_word)
+    }
+
+    /// File name of the source a merged row was read from, empty for rows of a single file.
+    fn source_name(&self, row: &Log) -> &str {
+        match row.source.and_then(|(source, _)| self.sources.get(source)) {
+            Some(source) => std::path::Path::new(&source.path).file_name().and_then(|name| name.to_str()).unwrap_or(&source.path),
+            None => "",
+        }
+    }
+
+    fn search_levels(&self) -> [bool; 5] {
+        [self.search_level_debug, self.search_level_info, self.search_level_warning, self.search_level_error, self.search_level_panic]
+    }
+
+    /// Narrows the filter to rows matching the current search, resetting the filter undoes it.
+    fn filter_to_matches(&mut self) {
+        self.filter_matches = Some(SearchTerms {
+            levels: self.search_levels(),
+            custom_levels: self.search_custom_levels.clone(),
+            message: self.search_message.clone(),
+            payload: self.search_payload.clone(),
+            caller: self.search_caller.clone(),
+            source: self.search_source.clone(),
+            time: self.search_time.clone(),
+        });
+        self.filter();
+    }
+
+    fn index_at_search_found_cursor(&self, index: usize) -> bool {
+        self.search_founds.get(self.search_found_cursor) == Some(&index)
+    }
+
+    fn search_reset(&mut self) {
+        self.search_level_debug = false;
+        self.search_level_info = false;
+        self.search_level_warning = false;
+        self.search_level_error = false;
+        self.search_level_panic = false;
+        self.search_custom_levels.clear();
+        self.search_message = "".to_string();
+        self.search_payload = "".to_string();
+        self.search_caller = "".to_string();
+        self.search_source = "".to_string();
+        self.search_time = "".to_string();
+        self.search_founds.clear();
+        self.search_scan = None;
+        self.search_cursor_line = None;
+        self.search_found_cursor = 0;
+    }
+
+    fn scroll_to_found(&mut self) {
+        if !self.search_keep_scroll {
+            self.scroll_to_row = self.search_founds.get(self.search_found_cursor).copied();
+        }
+    }
+
+    fn search_first(&mut self) {
+        self.search_cursor_line = None;
+        self.search_found_cursor = 0;
+        if self.search_founds.is_empty() {
+            self.scroll_to_row = None;
+            return;
+        }
+        self.scroll_to_found();
+    }
+
+    fn search_previous(&mut self) {
+        self.search_cursor_line = None;
+        if self.search_founds.is_empty() || self.search_found_cursor == 0 {
+            self.scroll_to_row = None;
+            return;
+        }
+        self.search_found_cursor -= 1;
+        self.scroll_to_found();
+    }
+
+    fn search_next(&mut self) {
+        self.search_cursor_line = None;
+        if self.search_founds.is_empty() || self.search_found_cursor >= self.search_founds.len() - 1 {
+            self.scroll_to_row = None;
+            return;
+        }
+        self.search_found_cursor += 1;
+        self.scroll_to_found();
+    }
+
+    fn search_last(&mut self) {
+        self.search_cursor_line = None;
+        if self.search_founds.is_empty() {
+            self.search_found_cursor = 0;
+            self.scroll_to_row = None;
+            return;
+        }
+        self.search_found_cursor = self.search_founds.len() - 1;
+        self.scroll_to_found();
+    }
+}
+
+
+#[derive(PartialEq, Clone, Copy)]
+enum DiffKind {
+    Same,
+    Removed,
+    Added,
+}
+
+impl DiffKind {
+    fn marker(self) -> &'static str {
+        match self {
+            DiffKind::Same => " ",
+            DiffKind::Removed => "−",
+            DiffKind::Added => "+",
+        }
+    }
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            DiffKind::Same => egui::Color32::GRAY,
+            DiffKind::Removed => egui::Color32::from_rgb(240, 80, 80),
+            DiffKind::Added => egui::Color32::from_rgb(80, 220, 80),
+        }
+    }
+}
+
+struct DiffRow {
+    kind: DiffKind,
+    index: usize,
+}
+
+
+/// Whether lines that failed to parse are shown among the others, hidden or shown alone.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum ParseErrorFilter {
+    Show,
+    Hide,
+    Only,
+}
+
+impl ParseErrorFilter {
+    fn name(self) -> &'static str {
+        match self {
+            ParseErrorFilter::Show => "Show",
+            ParseErrorFilter::Hide => "Hide",
+            ParseErrorFilter::Only => "Only",
+        }
+    }
+}
+
+/// What a single click on a row does.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum ClickAction {
+    Select,
+    Detail,
+    Both,
+}
+
+impl ClickAction {
+    fn name(self) -> &'static str {
+        match self {
+            ClickAction::Select => "Select",
+            ClickAction::Detail => "Open detail",
+            ClickAction::Both => "Select and open detail",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum Density {
+    Compact,
+    Normal,
+    Comfortable,
+}
+
+impl Density {
+    fn name(self) -> &'static str {
+        match self {
+            Density::Compact => "Compact",
+            Density::Normal => "Normal",
+            Density::Comfortable => "Comfortable",
+        }
+    }
+
+    fn row_height(self, ui: &egui::Ui) -> f32 {
+        let text_height = egui::TextStyle::Body.resolve(ui.style()).size;
+        match self {
+            Density::Compact => text_height + 2.0,
+            Density::Normal => text_height.max(ui.spacing().interact_size.y),
+            Density::Comfortable => text_height.max(ui.spacing().interact_size.y) + 8.0,
+        }
+    }
+
+    fn item_spacing(self, spacing: egui::Vec2) -> egui::Vec2 {
+        match self {
+            Density::Compact => egui::vec2(spacing.x * 0.5, 0.0),
+            Density::Normal => spacing,
+            Density::Comfortable => egui::vec2(spacing.x * 1.5, spacing.y * 2.0),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum ExportFormat {
+    Csv,
+    Ndjson,
+    /// Only used for the clipboard, spreadsheets take tab separated cells when pasting.
+    Tsv,
+}
+
+impl ExportFormat {
+    fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Ndjson => "NDJSON",
+            ExportFormat::Tsv => "TSV",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Tsv => "tsv",
+        }
+    }
+}
+
+/// Writes `logs` as CSV or TSV with the table columns, or as NDJSON lines rebuilt from the
+/// parsed fields with the payload keys at the top level. Times are written in `time_format`.
+fn write_export(mut writer: impl Write, logs: impl Iterator<Item = Log>, format: ExportFormat, time_format: TimeFormat) -> std::io::Result<()> {
+    let csv_field = |field: &str| {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    };
+    let tsv_field = |field: &str| field.replace(['\t', '\n', '\r'], " ");
+    match format {
+        ExportFormat::Csv => writeln!(writer, "time,level,message,payload,caller")?,
+        ExportFormat::Tsv => writeln!(writer, "time\tlevel\tmessage\tpayload\tcaller")?,
+        ExportFormat::Ndjson => {}
+    }
+    for log in logs {
+        let time = log.time.map(|_| time_format.display(&log)).unwrap_or_default();
+        match format {
+            ExportFormat::Csv => {
+                let fields = [time.as_str(), log.level_label(), &log.message, &log.payload, &log.caller].map(csv_field);
+                writeln!(writer, "{}", fields.join(","))?;
+            }
+            ExportFormat::Tsv => {
+                let fields = [time.as_str(), log.level_label(), &log.message, &log.payload, &log.caller].map(tsv_field);
+                writeln!(writer, "{}", fields.join("\t"))?;
+            }
+            ExportFormat::Ndjson => {
+                let mut line = serde_json::Map::new();
+                line.insert("ts".to_string(), log.time.map_or(serde_json::Value::Null, |_| time.into()));
+                line.insert("level".to_string(), log.level_label().into());
+                line.insert("msg".to_string(), log.message.into());
+                line.insert("caller".to_string(), log.caller.into());
+                if !log.stacktrace.is_empty() {
+                    line.insert("stacktrace".to_string(), log.stacktrace.into());
+                }
+                if let Ok(serde_json::Value::Object(payload)) = serde_json::from_str::<serde_json::Value>(&log.payload) {
+                    line.extend(payload);
+                }
+                writeln!(writer, "{}", serde_json::Value::Object(line))?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Writes a Markdown summary of `logs`: counts per level, the time span and the most
+/// frequent callers and messages.
+fn write_report(mut writer: impl Write, title: &str, logs: impl Iterator<Item = Log>) -> std::io::Result<()> {
+    let mut count = 0;
+    let mut levels = std::collections::BTreeMap::new();
+    let mut callers = std::collections::HashMap::new();
+    let mut messages = std::collections::HashMap::new();
+    let mut span: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> = None;
+    for log in logs {
+        count += 1;
+        *levels.entry(log.level).or_insert(0) += 1;
+        if !log.caller.is_empty() {
+            *callers.entry(log.caller).or_insert(0) += 1;
+        }
+        *messages.entry(log.message).or_insert(0) += 1;
+        if let Some(time) = log.time {
+            span = Some(span.map_or((time, time), |(first, last)| (first.min(time), last.max(time))));
+        }
+    }
+    let top = |counts: std::collections::HashMap<String, usize>| {
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by(|(a_text, a_count), (b_text, b_count)| b_count.cmp(a_count).then_with(|| a_text.cmp(b_text)));
+        counts.truncate(REPORT_TOP);
+        counts
+    };
+    let cell = |text: &str| text.replace('|', "\\|").replace(['\n', '\r'], " ");
+
+    writeln!(writer, "# {}", title)?;
+    writeln!(writer)?;
+    writeln!(writer, "- Rows: {}", count)?;
+    if let Some((first, last)) = span {
+        let seconds = (last - first).num_seconds();
+        let duration = format!("{}h {}m {}s", seconds / 3600, seconds / 60 % 60, seconds % 60);
+        writeln!(writer, "- Time span: {} → {} ({})", first.to_rfc3339(), last.to_rfc3339(), duration)?;
+    }
+    writeln!(writer)?;
+    writeln!(writer, "## Levels")?;
+    writeln!(writer)?;
+    writeln!(writer, "| Level | Count |")?;
+    writeln!(writer, "| --- | ---: |")?;
+    for (level, count) in levels {
+        let name = if level == Level::Unknown { "(none)" } else { level.to_string() };
+        writeln!(writer, "| {} | {} |", name, count)?;
+    }
+    for (heading, counts) in [("Top callers", callers), ("Top messages", messages)] {
+        writeln!(writer)?;
+        writeln!(writer, "## {}", heading)?;
+        writeln!(writer)?;
+        writeln!(writer, "| Count | Text |")?;
+        writeln!(writer, "| ---: | --- |")?;
+        for (text, count) in top(counts) {
+            writeln!(writer, "| {} | {} |", count, cell(&text))?;
+        }
+    }
+    writer.flush()
+}
+
+/// Rows sharing one value of the group key, by file line.
+struct LogGroup {
+    id: String,
+    lines: Vec<usize>,
+}
+
+/// Filtered rows with the same level, message and caller.
+struct UniqueLog {
+    level: Level,
+    custom_level: Option<std::sync::Arc<CustomLevel>>,
+    message: String,
+    caller: String,
+    count: usize,
+    first: Option<DateTime<FixedOffset>>,
+    last: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+enum SortColumn {
+    Time,
+    Level,
+    Message,
+    Caller,
+}
+
+impl SortColumn {
+    fn name(self) -> &'static str {
+        match self {
+            SortColumn::Time => "Time",
+            SortColumn::Level => "Level",
+            SortColumn::Message => "Message",
+            SortColumn::Caller => "Caller",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+struct Sort {
+    column: SortColumn,
+    descending: bool,
+}
+
+impl Sort {
+    fn name(sort: Option<Sort>) -> String {
+        match sort {
+            Some(sort) => format!("{} {}", sort.column.name(), if sort.descending { "⏷" } else { "⏶" }),
+            None => "File order".to_string(),
+        }
+    }
+
+    fn compare(self, a: &Log, b: &Log) -> std::cmp::Ordering {
+        let ordering = match self.column {
+            SortColumn::Time => a.time.cmp(&b.time),
+            SortColumn::Level => a.level.cmp(&b.level),
+            SortColumn::Message => a.message.cmp(&b.message),
+            SortColumn::Caller => a.caller.cmp(&b.caller),
+        };
+        if self.descending { ordering.reverse() } else { ordering }
+    }
+}
+
+/// A memory-mapped log file, only the line offsets and the filtered line numbers are kept in memory.
+/// The map and the offsets are shared with a running export.
+struct IndexedFile {
+    mmap: std::sync::Arc<memmap2::Mmap>,
+    line_starts: std::sync::Arc<Vec<usize>>,
+    filtered: Vec<usize>,
+}
+
+impl IndexedFile {
+    fn open(path: &str) -> Option<IndexedFile> {
+        let file = File::open(path).ok()?;
+        // SAFETY: the map is read only, a file truncated by another process while mapped may fault,
+        // which is the documented trade off of this mode.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.ok()?;
+        let mut line_starts = vec![0];
+        line_starts.extend(mmap.iter().enumerate().filter(|(_, &byte)| byte == b'\n').map(|(index, _)| index + 1));
+        if line_starts.last() == Some(&mmap.len()) {
+            line_starts.pop();
+        }
+        Some(IndexedFile {
+            mmap: std::sync::Arc::new(mmap),
+            line_starts: std::sync::Arc::new(line_starts),
+            filtered: vec![],
+        })
+    }
+
+    /// The same file with `lines` as its filtered lines.
+    fn with_lines(&self, lines: Vec<usize>) -> IndexedFile {
+        IndexedFile { mmap: self.mmap.clone(), line_starts: self.line_starts.clone(), filtered: lines }
+    }
+
+    fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    fn line(&self, index: usize) -> std::borrow::Cow<'_, str> {
+        let start = self.line_starts[index];
+        let end = self.line_starts.get(index + 1).copied().unwrap_or(self.mmap.len());
+        let line = String::from_utf8_lossy(&self.mmap[start..end]);
+        match line {
+            std::borrow::Cow::Borrowed(line) => std::borrow::Cow::Borrowed(line.trim_end()),
+            std::borrow::Cow::Owned(line) => std::borrow::Cow::Owned(line.trim_end().to_string()),
+        }
+    }
+
+    fn log(&self, index: usize, options: ParseOptions) -> Option<Log> {
+        let mut log = Log::from_line(&self.line(index), options)?;
+        log.line = index + 1;
+        log.index = index;
+        Some(log)
+    }
+}
+
+
+/// Time and Level cells of a rendered row, see `paint_frozen_columns`.
+struct FrozenRow {
+    time_rect: egui::Rect,
+    level_rect: egui::Rect,
+    time: String,
+    level: (String, egui::Color32),
+    index: usize,
+    found: bool,
+    selected: bool,
+}
+
+/// Repaints the Time and Level cells at the left edge of the horizontally scrolled
+/// `viewport`, so they stay visible next to wide columns.
+fn paint_frozen_columns(
+    ui: &egui::Ui,
+    viewport: egui::Rect,
+    shift: f32,
+    header: Option<(egui::Rect, egui::Rect)>,
+    rows: &[FrozenRow],
+    body_clip: egui::Rect,
+) {
+    let visuals = ui.visuals();
+    let font = cell_font(ui);
+    let painter = ui.painter_at(viewport);
+    let paint = |painter: &egui::Painter, time_rect: egui::Rect, level_rect: egui::Rect, fill: egui::Color32, time: (&str, egui::Color32), level: (&str, egui::Color32)| {
+        let moved = egui::vec2(shift, 0.0);
+        painter.rect_filled(time_rect.union(level_rect).translate(moved), 0.0, fill);
+        painter.text(time_rect.left_center() + moved, egui::Align2::LEFT_CENTER, time.0, font.clone(), time.1);
+        painter.text(level_rect.left_center() + moved, egui::Align2::LEFT_CENTER, level.0, font.clone(), level.1);
+    };
+
+    if let Some((time_rect, level_rect)) = header {
+        let strong = visuals.strong_text_color();
+        paint(&painter, time_rect, level_rect, visuals.panel_fill, ("Time", strong), ("Level", strong));
+    }
+    let body_painter = painter.with_clip_rect(body_clip.intersect(viewport));
+    for row in rows {
+        let fill = match (row.selected, row.index % 2) {
+            (true, _) => visuals.selection.bg_fill,
+            (false, 0) => visuals.faint_bg_color,
+            _ => visuals.panel_fill,
+        };
+        let time_color = if row.found { visuals.strong_text_color() } else { visuals.text_color() };
+        paint(&body_painter, row.time_rect, row.level_rect, fill, (&row.time, time_color), (&row.level.0, row.level.1));
+    }
+    if let Some((_, level_rect)) = header {
+        let x = level_rect.right() + shift;
+        painter.vline(x, viewport.y_range(), visuals.widgets.noninteractive.bg_stroke);
+    }
+}
+
+/// A JSON object or array payload pretty printed, any other payload as is.
+fn pretty_payload(payload: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(payload) {
+        Ok(value) if value.is_object() || value.is_array() => serde_json::to_string_pretty(&value).unwrap_or_default(),
+        _ => payload.to_string(),
+    }
+}
+
+/// The font labels of `ui` use, the body font unless the table overrides it with monospace.
+fn cell_font(ui: &egui::Ui) -> egui::FontId {
+    ui.style().override_text_style.clone().unwrap_or(egui::TextStyle::Body).resolve(ui.style())
+}
+
+/// Colors keys, strings, numbers and literals of a JSON text without parsing it, so
+/// the key order of the line is kept.
+fn json_highlight(json: &str, strong: bool, ui: &egui::Ui) -> egui::text::LayoutJob {
+    let font_id = cell_font(ui);
+    let punctuation = if strong { ui.visuals().strong_text_color() } else { ui.visuals().weak_text_color() };
+    let mut job = egui::text::LayoutJob::default();
+    let mut append = |text: &str, color: egui::Color32| {
+        job.append(text, 0.0, egui::TextFormat::simple(font_id.clone(), color));
+    };
+    let bytes = json.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let color = match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(bytes.len());
+                let is_key = json[i..].trim_start().starts_with(':');
+                if is_key { egui::Color32::from_rgb(100, 160, 240) } else { egui::Color32::from_rgb(150, 200, 110) }
+            }
+            b'-' | b'0'..=b'9' => {
+                while i < bytes.len() && matches!(bytes[i], b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') {
+                    i += 1;
+                }
+                egui::Color32::from_rgb(220, 160, 80)
+            }
+            b'a'..=b'z' => {
+                while i < bytes.len() && bytes[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                egui::Color32::from_rgb(190, 130, 220)
+            }
+            _ => {
+                // Step over a whole character, the input may contain multibyte text.
+                i += json[i..].chars().next().map_or(1, char::len_utf8);
+                punctuation
+            }
+        };
+        append(&json[start..i], color);
+    }
+    job
+}
 
-                            ui.separator();
-                            egui::Grid::new("search_grid")
-                                .num_columns(2)
-                                .spacing([40.0, 4.0])
-                                .striped(true)
-                                .show(ui, |ui| {
-                                    ui.strong("Search");
-                                    ui.horizontal(|ui| {
-                                        if ui.button("🔝").on_hover_text("First").clicked() {
-                                            self.search_first();
-                                        }
-                                        if ui.button("⬅").on_hover_text("Previous").clicked() {
-                                            self.search_previous();
-                                        }
-                                        if ui.button("➡").on_hover_text("Next").clicked() {
-                                            self.search_next();
-                                        }
-                                        if ui.button("🔚").on_hover_text("Last").clicked() {
-                                            self.search_last();
-                                        }
-                                        if ui.button("✖").on_hover_text("Reset").clicked() {
-                                            self.search_reset();
-                                        }
-                                    });
-                                    ui.end_row();
+/// Colors a message holding a JSON document or logfmt pairs, `None` for any other text.
+fn message_highlight(message: &str, strong: bool, ui: &egui::Ui) -> Option<egui::text::LayoutJob> {
+    let trimmed = message.trim_start();
+    if (trimmed.starts_with('{') || trimmed.starts_with('[')) && serde_json::from_str::<serde_json::Value>(message).is_ok() {
+        return Some(json_highlight(message, strong, ui));
+    }
+    if !is_logfmt(message) {
+        return None;
+    }
+    let font_id = cell_font(ui);
+    let punctuation = if strong { ui.visuals().strong_text_color() } else { ui.visuals().weak_text_color() };
+    let mut job = egui::text::LayoutJob::default();
+    let mut append = |text: &str, color: egui::Color32| {
+        job.append(text, 0.0, egui::TextFormat::simple(font_id.clone(), color));
+    };
+    for (index, (key, value)) in logfmt_pairs(message).into_iter().enumerate() {
+        if index > 0 {
+            append(" ", punctuation);
+        }
+        append(&key, egui::Color32::from_rgb(100, 160, 240));
+        if let Some(value) = value {
+            append("=", punctuation);
+            let value = if value.is_empty() || value.contains(char::is_whitespace) { format!("{:?}", value) } else { value };
+            append(&value, egui::Color32::from_rgb(150, 200, 110));
+        }
+    }
+    Some(job)
+}
 
-                                    ui.label("Level");
-                                    ui.horizontal(|ui| {
-                                        if ui.selectable_label(self.search_level_debug, "DEBUG").clicked() {
-                                            self.search_level_debug = !self.search_level_debug;
-                                            self.search();
-                                        }
-                                        if ui.selectable_label(self.search_level_info, "INFO").clicked() {
-                                            self.search_level_info = !self.search_level_info;
-                                            self.search();
-                                        }
-                                        if ui.selectable_label(self.search_level_warning, "WARNING").clicked() {
-                                            self.search_level_warning = !self.search_level_warning;
-                                            self.search();
-                                        }
-                                        if ui.selectable_label(self.search_level_error, "ERROR").clicked() {
-                                            self.search_level_error = !self.search_level_error;
-                                            self.search();
-                                        }
-                                        if ui.selectable_label(self.search_level_panic, "PANIC").clicked() {
-                                            self.search_level_panic = !self.search_level_panic;
-                                            self.search();
-                                        }
-                                    });
-                                    ui.end_row();
+/// Renders `value` as a tree, objects and arrays become collapsible headers.
+fn json_tree_ui(ui: &mut egui::Ui, key: &str, value: &serde_json::Value, id: egui::Id) {
+    match value {
+        serde_json::Value::Object(map) => {
+            egui::CollapsingHeader::new(format!("{} {{{}}}", key, map.len())).id_source(id).default_open(true).show(ui, |ui| {
+                for (key, value) in map {
+                    json_tree_ui(ui, key, value, id.with(key));
+                }
+            });
+        }
+        serde_json::Value::Array(items) => {
+            egui::CollapsingHeader::new(format!("{} [{}]", key, items.len())).id_source(id).default_open(true).show(ui, |ui| {
+                for (index, value) in items.iter().enumerate() {
+                    json_tree_ui(ui, &index.to_string(), value, id.with(index));
+                }
+            });
+        }
+        value => {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("{}:", key));
+                ui.monospace(value.to_string());
+            });
+        }
+    }
+}
 
-                                    ui.label("Message");
-                                    if ui.text_edit_singleline(&mut self.search_message).changed() {
-                                        self.search();
-                                    }
-                                    ui.end_row();
+/// Adds `name` to `names` or removes it when already there.
+fn toggle_name(names: &mut Vec<String>, name: &str) {
+    match names.iter().position(|other| other == name) {
+        Some(index) => {
+            names.remove(index);
+        }
+        None => names.push(name.to_string()),
+    }
+}
 
-                                    ui.label("Payload");
-                                    if ui.text_edit_singleline(&mut self.search_payload).changed() {
-                                        self.search();
-                                    }
-                                    ui.end_row();
+/// Shows the full `text` on hover when the cell clips its label or the label was `cut`.
+fn clipped_hover(ui: &egui::Ui, response: egui::Response, text: &str, cut: bool) {
+    let clip = ui.clip_rect();
+    if cut || response.rect.right() > clip.right() + 0.5 || response.rect.bottom() > clip.bottom() + 0.5 {
+        response.on_hover_ui(|ui| {
+            ui.set_max_width(600.0);
+            ui.label(text);
+        });
+    }
+}
 
-                                    ui.label("Caller");
-                                    if ui.text_edit_singleline(&mut self.search_caller).changed() {
-                                        self.search();
-                                    }
-                                    ui.end_row();
-                                });
-                        });
-                    }).response.on_hover_text("Filter & Search");
+/// A small × that empties `text`, returns whether it did.
+fn clear_button(ui: &mut egui::Ui, text: &mut String) -> bool {
+    let clicked = ui.add_enabled(!text.is_empty(), egui::Button::new("×").small()).named("Clear").clicked();
+    if clicked {
+        text.clear();
+    }
+    clicked
+}
 
-                    ui.label("Filtered");
-                    ui.monospace(self.filtered_logs.len().to_string());
-                    ui.label("from total");
-                    ui.monospace(self.logs.len().to_string());
-                });
+/// A "Copy value" entry for one cell, JSON objects and arrays can be copied minified or
+/// pretty printed and anything else as is.
+fn copy_value_ui(ui: &mut egui::Ui, name: &str, text: &str) {
+    let json = serde_json::from_str::<serde_json::Value>(text).ok().filter(|value| value.is_object() || value.is_array());
+    match json {
+        Some(value) => {
+            ui.menu_button(name, |ui| {
+                for (format, copied) in [("Minified", value.to_string()), ("Pretty", serde_json::to_string_pretty(&value).unwrap_or_default())] {
+                    if ui.button(format).clicked() {
+                        ui.ctx().copy_text(copied);
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+        None => {
+            if ui.add_enabled(!text.is_empty(), egui::Button::new(name)).clicked() {
+                ui.ctx().copy_text(text.to_string());
+                ui.close_menu();
             }
+        }
+    }
+}
 
-            ui.separator();
+/// The last component of `path`, or all of it.
+fn file_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
 
-            let body_text_size = egui::TextStyle::Body.resolve(ui.style()).size;
-            use egui_extras::{Size, StripBuilder};
-            StripBuilder::new(ui)
-                .size(Size::remainder().at_least(0.0))
-                .size(Size::exact(body_text_size))
-                .vertical(|mut strip| {
-                    strip.cell(|ui| {
-                        egui::ScrollArea::horizontal().show(ui, |ui| {
-                            use egui_extras::{Column, TableBuilder};
+/// The field after `focused` in `chain`, or before it going back, none at either end.
+fn focus_step(chain: &[egui::Id], focused: egui::Id, back: bool) -> Option<egui::Id> {
+    let index = chain.iter().position(|id| *id == focused)?;
+    if back {
+        index.checked_sub(1).map(|index| chain[index])
+    } else {
+        chain.get(index + 1).copied()
+    }
+}
 
-                            let text_height = egui::TextStyle::Body
-                                .resolve(ui.style())
-                                .size
-                                .max(ui.spacing().interact_size.y);
+fn shortcuts_ui(ui: &mut egui::Ui) {
+    let command = |key| ui.ctx().format_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, key));
+    let shortcuts = [
+        (command(egui::Key::F), "Find"),
+        (command(egui::Key::Z), "Undo filter change"),
+        (command(egui::Key::Y), "Redo filter change"),
+        (ui.ctx().format_shortcut(&RESET_FILTER_SHORTCUT), "Reset filter"),
+        (ui.ctx().format_shortcut(&RESET_SEARCH_SHORTCUT), "Reset search"),
+        (ui.ctx().format_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::ArrowDown)), "Next error"),
+        (ui.ctx().format_shortcut(&egui::KeyboardShortcut::new(egui::Modifiers::ALT, egui::Key::ArrowUp)), "Previous error"),
+        ("↑ ↓".to_string(), "Previous or next row in the detail panel"),
+        ("Double click".to_string(), "Fit the column left of a header divider"),
+        ("Tab Shift+Tab".to_string(), "Next or previous filter and search field"),
+    ];
+    egui::Grid::new("shortcuts_grid").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+        for (keys, action) in shortcuts {
+            ui.monospace(keys);
+            ui.label(action);
+            ui.end_row();
+        }
+    });
+}
 
-                            let mut table = TableBuilder::new(ui)
-                                .striped(true)
-                                .resizable(true)
-                                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-                                .column(Column::exact(200.0))
-                                .column(Column::exact(50.0))
-                                .column(Column::initial(100.00).at_least(100.0))
-                                .column(Column::initial(100.00).at_least(100.0))
-                                .column(Column::remainder())
-                                .min_scrolled_height(0.0)
-                                .max_scroll_height(2000.0);
+/// `text` cut to `max` characters with an ellipsis, or `None` when it already fits or
+/// `max` is 0.
+fn truncate_chars(text: &str, max: usize) -> Option<String> {
+    if max == 0 {
+        return None;
+    }
+    let (end, _) = text.char_indices().nth(max)?;
+    Some(format!("{}…", &text[..end]))
+}
 
-                            table = table.sense(egui::Sense::click());
+/// Hover text that doubles as the name screen readers announce, for controls showing
+/// only an icon.
+trait Named {
+    fn named(self, name: &str) -> Self;
+    fn named_toggle(self, selected: bool, name: &str) -> Self;
+}
 
-                            if let Some(row_index) = self.search_found_scroll_row.take() {
-                                table = table.scroll_to_row(row_index, None);
-                            }
+impl Named for egui::Response {
+    fn named(self, name: &str) -> Self {
+        self.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, name));
+        self.on_hover_text(name)
+    }
 
-                            table
-                                .header(20.0, |mut header| {
-                                    header.col(|ui| {
-                                        ui.strong("Time");
-                                    });
-                                    header.col(|ui| {
-                                        ui.strong("Level");
-                                    });
-                                    header.col(|ui| {
-                                        ui.strong("Message");
-                                    });
-                                    header.col(|ui| {
-                                        ui.strong("Payload");
-                                    });
-                                    header.col(|ui| {
-                                        ui.strong("Caller");
-                                    });
-                                })
-                                .body(|body| {
-                                    body.rows(text_height, self.filtered_logs.len(), |mut row| {
-                                        let row_index = row.index();
-                                        row.set_selected(self.selection.contains(&row_index) || self.index_at_search_found_cursor(row_index));
+    fn named_toggle(self, selected: bool, name: &str) -> Self {
+        self.widget_info(|| egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, selected, name));
+        self.on_hover_text(name)
+    }
+}
 
-                                        let found_on_search = self.search_founds.contains(&row_index);
+/// Fetches `url` into the temp directory and returns the file to open, gzip bodies are
+/// unpacked so they go through the same reader as any other log.
+fn download(url: &str, events: &std::sync::mpsc::Sender<DownloadEvent>, ctx: &egui::Context) -> Result<std::path::PathBuf, String> {
+    let response = ureq::get(url).call().map_err(|err| err.to_string())?;
+    let total = response.header("Content-Length").and_then(|length| length.parse().ok());
+    let name = url.split(['?', '#']).next().unwrap_or(url).rsplit('/').find(|part| !part.is_empty() && !part.contains(':')).unwrap_or("download.log");
+    let dir = std::env::temp_dir().join(format!("lvx-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    let raw_path = dir.join(format!("{}.part", name));
 
-                                        row.col(|ui| {
-                                            let ts = self.filtered_logs[row_index].time.to_rfc3339();
-                                            if found_on_search { ui.strong(ts); } else { ui.label(ts); }
-                                        });
-                                        row.col(|ui| {
-                                            let level = self.filtered_logs[row_index].level.clone();
-                                            let mut color = egui::Color32::from_rgb(80, 80, 80);
-                                            match level {
-                                                Level::Debug => { color = egui::Color32::from_rgb(10, 10, 240); }
-                                                Level::Info => { color = egui::Color32::from_rgb(10, 240, 10); }
-                                                Level::Warning => { color = egui::Color32::from_rgb(240, 240, 10); }
-                                                Level::Error => { color = egui::Color32::from_rgb(240, 60, 10); }
-                                                Level::Panic => { color = egui::Color32::from_rgb(240, 10, 10); }
-                                                _ => {}
-                                            }
-                                            ui.colored_label(color, level.to_string());
-                                        });
-                                        row.col(|ui| {
-                                            let msg = self.filtered_logs[row_index].message.to_string();
-                                            if found_on_search { ui.strong(msg); } else { ui.label(msg); }
-                                        });
-                                        row.col(|ui| {
-                                            let py = self.filtered_logs[row_index].payload.to_string();
-                                            if found_on_search { ui.strong(py); } else { ui.label(py); }
-                                        });
-                                        row.col(|ui| {
-                                            let ca = self.filtered_logs[row_index].caller.to_string();
-                                            if found_on_search { ui.strong(ca); } else { ui.label(ca); }
-                                        });
+    let mut reader = response.into_reader();
+    let mut writer = BufWriter::new(File::create(&raw_path).map_err(|err| err.to_string())?);
+    let mut buffer = vec![0; 64 * 1024];
+    let mut received = 0;
+    loop {
+        let read = reader.read(&mut buffer).map_err(|err| err.to_string())?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read]).map_err(|err| err.to_string())?;
+        received += read as u64;
+        let _ = events.send(DownloadEvent::Progress { received, total });
+        ctx.request_repaint();
+    }
+    writer.flush().map_err(|err| err.to_string())?;
+    drop(writer);
 
-                                        self.toggle_row_selection(row_index, &row.response());
-                                    });
-                                })
-                        });
-                    });
-                });
-        });
+    let mut magic = [0; 2];
+    let gzip = File::open(&raw_path).and_then(|mut file| file.read_exact(&mut magic)).is_ok() && magic == [0x1f, 0x8b];
+    if !gzip {
+        let path = dir.join(name);
+        std::fs::rename(&raw_path, &path).map_err(|err| err.to_string())?;
+        return Ok(path);
     }
+    let path = dir.join(name.strip_suffix(".gz").unwrap_or(name));
+    let result = File::open(&raw_path).and_then(|file| {
+        let mut decoder = flate2::read::MultiGzDecoder::new(BufReader::new(file));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        std::io::copy(&mut decoder, &mut writer)?;
+        writer.flush()
+    });
+    let _ = std::fs::remove_file(&raw_path);
+    result.map_err(|err| err.to_string())?;
+    Ok(path)
 }
 
+/// Fits the widest timestamp of the given format, with nanoseconds and a zone offset.
+fn time_column_width(ui: &egui::Ui, utc: bool, precision: TimePrecision) -> f32 {
+    let widest = DateTime::parse_from_rfc3339("2000-12-28T20:58:58.888888888+10:00").ok();
+    let text = format_time(widest, utc, precision);
+    let font_id = cell_font(ui);
+    let width = ui.fonts(|f| f.layout_no_wrap(text, font_id, egui::Color32::WHITE).size().x);
+    (width + ui.spacing().item_spacing.x * 2.0).max(100.0)
+}
 
-impl App {
-    fn toggle_row_selection(&mut self, row_index: usize, row_response: &egui::Response) {
-        if row_response.clicked() {
-            if self.selection.contains(&row_index) {
-                self.selection.remove(&row_index);
-            } else {
-                self.selection.insert(row_index);
-            }
-        }
+/// `+1.204s` style offset, minutes and hours are added once it gets that long.
+fn format_offset(offset: chrono::Duration) -> String {
+    let sign = if offset < chrono::Duration::zero() { '-' } else { '+' };
+    let millis = offset.num_milliseconds().unsigned_abs();
+    let (hours, minutes, seconds) = (millis / 3_600_000, millis / 60_000 % 60, millis % 60_000);
+    match (hours, minutes) {
+        (0, 0) => format!("{}{}.{:03}s", sign, seconds / 1000, seconds % 1000),
+        (0, _) => format!("{}{}m{:02}.{:03}s", sign, minutes, seconds / 1000, seconds % 1000),
+        _ => format!("{}{}h{:02}m{:02}.{:03}s", sign, hours, minutes, seconds / 1000, seconds % 1000),
     }
+}
 
-    fn read_file(&mut self) {
-        self.logs.clear();
-        if let Some(path) = &self.picked_path {
-            let buffer = Box::new(BufReader::new(File::open(path.to_string()).unwrap()));
-            for line in buffer.lines() {
-                if let Ok(json_str) = line {
-                    if let Ok(value) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                        if let Ok(json_line) = serde_json::from_value::<JsonLine>(value) {
-                            let mut payload = String::from("");
-                            if !json_line.payload.is_empty() {
-                                let mut keys: Vec<_> = json_line.payload.keys().cloned().collect();
-                                keys.sort();
-                                let mut sorted = serde_json::json!({});
-                                for key in keys {
-                                    sorted[key.clone()] = json_line.payload[&key].clone();
-                                }
-                                payload = sorted.to_string()
-                            }
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
 
-                            self.logs.push(Log {
-                                time: Log::time_from_string(json_line.ts),
-                                level: Level::from_string(json_line.level.as_str()),
-                                message: json_line.msg,
-                                payload: payload.to_string(),
-                                caller: json_line.caller,
-                            });
-                        }
+/// URLs, and `file:line` references with a source file extension so that host names with a
+/// port are left alone.
+fn link_regex() -> regex::Regex {
+    let extensions = "go|rs|py|js|jsx|ts|tsx|java|kt|scala|c|h|cc|cpp|hpp|cs|rb|php|swift|ex|exs|erl|lua|dart|sh";
+    regex::Regex::new(&format!(r#"https?://[^\s"'<>`]+|(?:\.{{0,2}}/)?(?:[\w.\-]+/)*[\w\-]+\.(?:{}):\d+\b"#, extensions)).unwrap()
+}
+
+enum LinkPart<'a> {
+    Text(&'a str),
+    Url(&'a str),
+    File(&'a str),
+}
+
+fn link_parts<'a>(links: &regex::Regex, text: &'a str) -> Vec<LinkPart<'a>> {
+    let mut parts = vec![];
+    let mut at = 0;
+    for found in links.find_iter(text) {
+        let url = found.as_str().starts_with("http");
+        let link = match url {
+            // Punctuation after a URL usually ends the sentence around it.
+            true => found.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}']),
+            false => found.as_str(),
+        };
+        // A file reference has to start a word, `main.go:1` inside `pkg.main.go:1` is not one.
+        let glued = text[..found.start()].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        if (!url && glued) || link.is_empty() {
+            continue;
+        }
+        if found.start() > at {
+            parts.push(LinkPart::Text(&text[at..found.start()]));
+        }
+        parts.push(if url { LinkPart::Url(link) } else { LinkPart::File(link) });
+        at = found.start() + link.len();
+    }
+    if at < text.len() {
+        parts.push(LinkPart::Text(&text[at..]));
+    }
+    parts
+}
+
+/// Lays out `text` with its URLs as hyperlinks and, given an `editor` command, its
+/// `file:line` references as links that open it. None when there is nothing to link.
+/// An editor that fails to start leaves its error in `editor_error`.
+fn linkified_ui(ui: &mut egui::Ui, links: &regex::Regex, text: &str, strong: bool, editor: Option<&str>, editor_error: &mut Option<String>) -> Option<egui::Response> {
+    let parts = link_parts(links, text);
+    let linkable = |part: &LinkPart| matches!(part, LinkPart::Url(_)) || (matches!(part, LinkPart::File(_)) && editor.is_some());
+    if !parts.iter().any(linkable) {
+        return None;
+    }
+    let response = ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for part in parts {
+            match part {
+                LinkPart::Url(url) => {
+                    ui.hyperlink(url);
+                }
+                LinkPart::File(file) if editor.is_some() => {
+                    if ui.link(file).on_hover_text("Open in editor").clicked() {
+                        *editor_error = open_in_editor(editor.unwrap_or_default(), file).err();
                     }
                 }
+                LinkPart::Text(text) | LinkPart::File(text) => {
+                    if strong { ui.strong(text); } else { ui.label(text); }
+                }
             }
-            self.filter_reset();
         }
+    });
+    Some(response.response)
+}
+
+/// Splits a `file.go:123` caller into its file and line.
+fn caller_location(caller: &str) -> Option<(&str, &str)> {
+    caller.rsplit_once(':').filter(|(file, line)| !file.is_empty() && line.parse::<u32>().is_ok())
+}
+
+/// The platform's player and its arguments for the alert sound of follow mode.
+#[cfg(windows)]
+const ALERT_SOUND: &[&str] = &["powershell", "-NoProfile", "-Command", "[System.Media.SystemSounds]::Exclamation.Play()"];
+#[cfg(target_os = "macos")]
+const ALERT_SOUND: &[&str] = &["afplay", "/System/Library/Sounds/Sosumi.aiff"];
+#[cfg(not(any(windows, target_os = "macos")))]
+const ALERT_SOUND: &[&str] = &["canberra-gtk-play", "--id=bell"];
+
+/// Starts `command` with its output discarded, without waiting for the sound to end.
+fn play_sound(command: &[&str]) -> Result<(), String> {
+    let (program, args) = command.split_first().ok_or("No sound player")?;
+    std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|err| format!("{}: {}", program, err))?;
+    Ok(())
+}
+
+/// Runs the editor `template` for `caller`, the file is appended when the template has no `{file}`.
+fn open_in_editor(template: &str, caller: &str) -> Result<(), String> {
+    let Some((file, line)) = caller_location(caller) else {
+        return Ok(());
+    };
+    let mut args: Vec<String> = template
+        .split_whitespace()
+        .flat_map(|arg| match arg.strip_prefix('$') {
+            Some(var) => std::env::var(var).unwrap_or_default().split_whitespace().map(str::to_string).collect(),
+            None => vec![arg.to_string()],
+        })
+        .collect();
+    if args.is_empty() {
+        return Err(format!("'{}' names no program, set one under Open caller", template));
+    }
+    if !template.contains("{file}") {
+        args.push("{file}".to_string());
     }
+    let args: Vec<String> = args.iter().map(|arg| arg.replace("{file}", file).replace("{line}", line)).collect();
+    let (program, args) = args.split_first().ok_or("no program")?;
+    std::process::Command::new(program).args(args).spawn().map_err(|err| format!("{}: {}", program, err))?;
+    Ok(())
+}
 
-    fn filter(&mut self) {
-        self.filtered_logs = self.logs.iter()
-            .filter(|row| {
-                let mut level = row.level == Level::Unknown;
-                level |= row.level == Level::Debug && self.filter_level_debug;
-                level |= row.level == Level::Info && self.filter_level_info;
-                level |= row.level == Level::Warning && self.filter_level_warning;
-                level |= row.level == Level::Error && self.filter_level_error;
-                level |= row.level == Level::Panic && self.filter_level_panic;
-                let message = row.message.to_lowercase().contains(&self.filter_message.to_lowercase());
-                let payload = row.payload.to_lowercase().contains(&self.filter_payload.to_lowercase());
-                let caller = row.caller.to_lowercase().contains(&self.filter_caller.to_lowercase());
-                level && message && payload && caller
-            })
-            .cloned()
-            .collect::<Vec<_>>();
-        self.search();
+fn pair_mut<T>(items: &mut [T], a: usize, b: usize) -> (&mut T, &mut T) {
+    if a < b {
+        let (head, tail) = items.split_at_mut(b);
+        (&mut head[a], &mut tail[0])
+    } else {
+        let (head, tail) = items.split_at_mut(a);
+        (&mut tail[0], &mut head[b])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tab_with_founds(founds: &[usize]) -> Tab {
+        Tab { search_founds: founds.to_vec(), ..Default::default() }
     }
 
-    fn filter_reset(&mut self) {
-        self.filter_level_debug = true;
-        self.filter_level_info = true;
-        self.filter_level_warning = true;
-        self.filter_level_error = true;
-        self.filter_level_panic = true;
-        self.filter_message = "".to_string();
-        self.filter_payload = "".to_string();
-        self.filter_caller = "".to_string();
-        self.filter();
+    /// A fresh file under the temp directory, named after the test so tests can run in parallel.
+    fn temp_log(name: &str, text: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("lvx-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, text).unwrap();
+        path.to_string_lossy().into_owned()
     }
 
-    fn search(&mut self) {
-        if !self.search_level_debug
-            && !self.search_level_info
-            && !self.search_level_warning
-            && !self.search_level_error
-            && !self.search_level_panic
-            && self.search_message.is_empty()
-            && self.search_payload.is_empty()
-            && self.search_caller.is_empty() {
-            self.search_reset();
-            return;
-        }
+    fn json_lines(range: std::ops::Range<usize>) -> String {
+        range.map(|i| format!("{{\"ts\":\"2024-03-01T10:32:05Z\",\"level\":\"{}\",\"msg\":\"m{}\"}}\n", if i % 2 == 0 { "INFO" } else { "ERROR" }, i)).collect()
+    }
 
-        self.search_founds.clear();
-        for (index, row) in self.filtered_logs.iter().enumerate() {
-            let mut level = row.level == Level::Unknown;
-            level |= row.level == Level::Debug && self.search_level_debug;
-            level |= row.level == Level::Info && self.search_level_info;
-            level |= row.level == Level::Warning && self.search_level_warning;
-            level |= row.level == Level::Error && self.search_level_error;
-            level |= row.level == Level::Panic && self.search_level_panic;
-            let message = row.message.to_lowercase().contains(&self.search_message.to_lowercase());
-            let payload = row.payload.to_lowercase().contains(&self.search_payload.to_lowercase());
-            let caller = row.caller.to_lowercase().contains(&self.search_caller.to_lowercase());
+    fn append(path: &str, text: &str) {
+        std::fs::OpenOptions::new().append(true).open(path).unwrap().write_all(text.as_bytes()).unwrap();
+    }
 
-            if level && message && payload && caller {
-                self.search_founds.push(index)
+    fn open(path: &str) -> Tab {
+        let mut tab = Tab { picked_path: path.to_string(), ..Default::default() };
+        tab.read_file();
+        tab
+    }
+
+    /// Whether a reader still holds the FIFO at `path` open, waiting up to a second for it to let go.
+    #[cfg(unix)]
+    fn fifo_reader_gone(path: &str) -> bool {
+        use std::os::unix::fs::OpenOptionsExt;
+        let started = std::time::Instant::now();
+        while started.elapsed() < std::time::Duration::from_secs(1) {
+            // Opening a FIFO to write without waiting fails when nothing reads it.
+            if std::fs::OpenOptions::new().write(true).custom_flags(libc::O_NONBLOCK).open(path).is_err() {
+                return true;
             }
+            std::thread::sleep(std::time::Duration::from_millis(10));
         }
+        false
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipe_reader_ends_with_its_tab() {
+        let path = std::env::temp_dir().join(format!("lvx-test-{}", std::process::id())).join("pipe.fifo");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        let path = path.to_str().unwrap();
 
-        self.search_found_cursor = 0
+        // Closed before any writer connected.
+        let pipe = Pipe::open(path);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        drop(pipe);
+        assert!(fifo_reader_gone(path));
+
+        let pipe = Pipe::open(path);
+        let mut writer = std::fs::OpenOptions::new().write(true).open(path).unwrap();
+        writer.write_all(b"one\ntw").unwrap();
+        let timeout = std::time::Duration::from_secs(1);
+        assert_eq!(pipe.lines.recv_timeout(timeout).unwrap(), b"one\n");
+        writer.write_all(b"o\n").unwrap();
+        assert_eq!(pipe.lines.recv_timeout(timeout).unwrap(), b"two\n");
+        // Closed while a writer is still connected.
+        drop(pipe);
+        assert!(fifo_reader_gone(path));
     }
 
-    fn index_at_search_found_cursor(&mut self, index: usize) -> bool {
-        if self.search_founds.is_empty() {
-            return false;
-        }
-        if self.search_found_cursor > self.search_founds.len() - 1 {
-            return false;
-        }
-        return index == self.search_founds[self.search_found_cursor];
+    #[test]
+    fn drop_oldest_keeps_indexes_unique() {
+        let path = temp_log("drop_oldest.log", &json_lines(0..10));
+        let mut tab = open(&path);
+        tab.filter_level_info = false;
+        tab.filter();
+        tab.search_message = "m".to_string();
+        tab.search_level_error = true;
+        tab.search();
+        while tab.search_step(std::time::Duration::from_secs(1)) {}
+        append(&path, &json_lines(10..20));
+        tab.follow_poll(Some(8));
+        assert_eq!(tab.logs.len(), 8);
+        assert_eq!(tab.lines_dropped, 12);
+        let messages: Vec<&str> = tab.filtered_logs.iter().map(|log| log.message.as_str()).collect();
+        assert_eq!(messages, ["m13", "m15", "m17", "m19"]);
+        assert_eq!(tab.search_founds, [0, 1, 2, 3]);
+        let indexes: Vec<usize> = tab.logs.iter().map(|log| log.index).collect();
+        assert_eq!(indexes, (12..20).collect::<Vec<_>>());
     }
 
-    fn search_reset(&mut self) {
-        self.search_level_debug = false;
-        self.search_level_info = false;
-        self.search_level_warning = false;
-        self.search_level_error = false;
-        self.search_level_panic = false;
-        self.search_message = "".to_string();
-        self.search_payload = "".to_string();
-        self.search_caller = "".to_string();
-        self.search_founds.clear();
+    #[test]
+    fn drop_oldest_skips_stale_matches() {
+        let path = temp_log("drop_stale.log", &json_lines(0..12));
+        let mut tab = open(&path);
+        tab.search_founds = vec![1, 11, 40];
+        tab.search_found_cursor = 2;
+        tab.drop_oldest(5);
+        assert_eq!(tab.search_founds, [4]);
+        assert_eq!(tab.search_found_cursor, 0);
     }
 
-    fn search_first(&mut self) {
-        self.search_found_cursor = 0;
-        if self.search_founds.is_empty() {
-            self.search_found_scroll_row = None;
-            return;
-        }
-        self.search_found_scroll_row = Some(self.search_founds[self.search_found_cursor]);
+    #[test]
+    fn export_writes_the_time_format() {
+        let options = ParseOptions { format: LogFormat::Json, text_pattern: None, sort_payload: false, skip_comments: false, custom_levels: &[] };
+        let log = Log::from_line(r#"{"ts":"2024-03-01T10:32:05.123+02:00","level":"INFO","msg":"a"}"#, options).unwrap();
+        let export = |format, time_format| {
+            let mut out = vec![];
+            write_export(&mut out, std::iter::once(log.clone()), format, time_format).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let utc = TimeFormat { utc: true, precision: TimePrecision::Seconds, ..Default::default() };
+        assert_eq!(export(ExportFormat::Csv, utc), "time,level,message,payload,caller\n2024-03-01T08:32:05+00:00,INFO,a,,\n");
+        assert!(export(ExportFormat::Ndjson, utc).starts_with(r#"{"ts":"2024-03-01T08:32:05+00:00""#));
+        let verbatim = TimeFormat { verbatim: true, ..utc };
+        assert!(export(ExportFormat::Tsv, verbatim).contains("\n2024-03-01T10:32:05.123+02:00\tINFO"));
     }
 
-    fn search_previous(&mut self) {
-        if self.search_founds.is_empty() || self.search_found_cursor <= 0 {
-            self.search_found_scroll_row = None;
-            return;
-        }
-        self.search_found_cursor -= 1;
-        self.search_found_scroll_row = Some(self.search_founds[self.search_found_cursor]);
+    #[test]
+    fn export_rows_shares_the_rows() {
+        let path = temp_log("export_rows.log", &json_lines(0..4));
+        let tab = open(&path);
+        let rows = tab.export_rows(vec![3, 1, 9]);
+        assert_eq!(std::sync::Arc::strong_count(&tab.filtered_logs[3]), 2);
+        let messages: Vec<String> = rows.map(|log| log.message).collect();
+        assert_eq!(messages, ["m3", "m1"]);
+        let mut indexed = Tab { picked_path: path, indexed_mode: true, ..Default::default() };
+        indexed.read_file();
+        assert!(indexed.indexed.is_some());
+        let messages: Vec<String> = indexed.export_rows(vec![2, 0]).map(|log| log.message).collect();
+        assert_eq!(messages, ["m2", "m0"]);
     }
 
-    fn search_next(&mut self) {
-        if self.search_founds.is_empty() || self.search_found_cursor >= self.search_founds.len() - 1 {
-            self.search_found_scroll_row = None;
-            return;
-        }
-        self.search_found_cursor += 1;
-        self.search_found_scroll_row = Some(self.search_founds[self.search_found_cursor]);
+    #[test]
+    fn selection_follows_its_rows_through_the_filter() {
+        let path = temp_log("selection.log", &json_lines(0..6));
+        let mut tab = open(&path);
+        // m1 and m4, the second one is hidden by the filter below.
+        tab.selection = [1, 4].into();
+        tab.filter_level_info = false;
+        tab.filter();
+        assert_eq!(tab.selection, [0].into());
+        assert_eq!(tab.filtered_logs[0].message, "m1");
+        tab.filter_level_info = true;
+        tab.filter();
+        assert_eq!(tab.selection, [1].into());
     }
 
-    fn search_last(&mut self) {
-        if self.search_founds.is_empty() {
-            self.search_found_cursor = 0;
-            self.search_found_scroll_row = None;
-        }
-        self.search_found_cursor = self.search_founds.len() - 1;
-        self.search_found_scroll_row = Some(self.search_founds[self.search_found_cursor]);
+    #[test]
+    fn sort_after_search_moves_the_matches() {
+        let path = temp_log("sort_search.log", &json_lines(0..6));
+        let mut tab = open(&path);
+        tab.search_level_error = true;
+        tab.search();
+        while tab.search_step(std::time::Duration::from_secs(1)) {}
+        assert_eq!(tab.search_founds, [1, 3, 5]);
+        tab.search_next();
+        tab.selection = [0].into();
+        tab.apply_sort(Sort { column: SortColumn::Message, descending: true });
+        let messages: Vec<&str> = tab.filtered_logs.iter().map(|log| log.message.as_str()).collect();
+        assert_eq!(messages, ["m5", "m4", "m3", "m2", "m1", "m0"]);
+        assert_eq!(tab.search_founds, [0, 2, 4]);
+        // The cursor stays on m3, the selection on m0.
+        assert_eq!(tab.filtered_logs[tab.search_founds[tab.search_found_cursor]].message, "m3");
+        assert_eq!(tab.selection, [5].into());
     }
-}
 
+    #[test]
+    fn headless_level_filter_drops_rows_without_a_level() {
+        let input = temp_log("headless.log", &format!("{}not json at all\n# comment\n", json_lines(0..4)));
+        let output = temp_log("headless.ndjson", "");
+        let args: Vec<String> = ["--export", &output, "--filter-level", "error", &input].map(str::to_string).to_vec();
+        let rows = export_headless(ExportArgs::parse(&args).unwrap(), &Config::default()).unwrap();
+        assert_eq!(rows, 2);
+        let written = std::fs::read_to_string(&output).unwrap();
+        let messages: Vec<String> = written.lines().map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["msg"].to_string()).collect();
+        assert_eq!(messages, [r#""m1""#, r#""m3""#]);
+    }
 
-#[derive(PartialEq, Clone)]
-enum Level {
-    Unknown,
-    Debug,
-    Info,
-    Warning,
-    Error,
-    Panic,
-}
+    #[test]
+    fn poll_reloads_a_file_that_shrank_then_grew() {
+        let path = temp_log("shrink.log", &json_lines(0..10));
+        let mut tab = Tab { auto_poll: true, ..open(&path) };
+        std::fs::write(&path, json_lines(20..22)).unwrap();
+        tab.follow_poll(None);
+        assert!(tab.rotated_at.is_some());
+        assert_eq!(tab.rotated, Some((10, 2)));
+        append(&path, &json_lines(22..25));
+        tab.follow_poll(None);
+        let messages: Vec<&str> = tab.logs.iter().map(|log| log.message.as_str()).collect();
+        assert_eq!(messages, ["m20", "m21", "m22", "m23", "m24"]);
+        let lines: Vec<usize> = tab.logs.iter().map(|log| log.line).collect();
+        assert_eq!(lines, [1, 2, 3, 4, 5]);
+        assert_eq!(tab.filtered_logs.len(), 5);
+        assert_eq!(tab.lines_read, 5);
+    }
 
-impl Level {
-    fn from_string(level: &str) -> Level {
-        match level {
-            "DEBUG" => Level::Debug,
-            "INFO" => Level::Info,
-            "WARN" => Level::Warning,
-            "ERROR" => Level::Error,
-            "PANIC" => Level::Panic,
-            _ => Level::Unknown,
-        }
+    #[test]
+    fn malformed_last_line_without_newline_is_kept() {
+        let path = temp_log("partial.log", &(json_lines(0..2) + "{\"ts\": 3, broken"));
+        let tab = open(&path);
+        assert_eq!(tab.logs.len(), 3);
+        assert!(tab.logs[2].parse_error);
+        assert_eq!(tab.lines_read, 3);
+        let path = temp_log("partial_followed.log", &(json_lines(0..2) + "{\"ts\""));
+        let mut followed = Tab { picked_path: path, follow: true, ..Default::default() };
+        followed.read_file();
+        assert_eq!(followed.logs.len(), 2);
+        assert_eq!(followed.lines_read, 2);
     }
-    fn to_string(self) -> &'static str {
-        match self {
-            Level::Debug => "DEBUG",
-            Level::Info => "INFO",
-            Level::Warning => "WARN",
-            Level::Error => "ERROR",
-            Level::Panic => "PANIC",
-            _ => "N/A",
-        }
+
+    #[test]
+    fn config_save_reports_failures() {
+        // The only test that reads the config directory.
+        let blocked = temp_log("config_blocked", "");
+        std::env::set_var("XDG_CONFIG_HOME", &blocked);
+        assert!(Config::default().save().is_err());
+        let dir = std::path::Path::new(&blocked).with_file_name("config_home");
+        std::env::set_var("XDG_CONFIG_HOME", &dir);
+        let saved = Config { utc_time: Some(true), ..Default::default() }.save().unwrap();
+        assert_eq!(saved, dir.join("lvx").join("lvx.toml"));
+        assert!(std::fs::read_to_string(saved).unwrap().contains("utc_time = true"));
     }
-}
 
+    #[test]
+    fn open_in_editor_reports_failures() {
+        assert!(open_in_editor("lvx-no-such-editor {file}", "main.go:12").unwrap_err().starts_with("lvx-no-such-editor: "));
+        assert!(open_in_editor("$LVX_UNSET_EDITOR", "main.go:12").is_err());
+        // Callers without a line are not links, nothing is started.
+        assert!(open_in_editor("lvx-no-such-editor", "main.go").is_ok());
+    }
 
-#[derive(Clone)]
-struct Log {
-    time: DateTime<Local>,
-    level: Level,
-    message: String,
-    caller: String,
-    payload: String,
-}
+    #[test]
+    fn play_sound_reports_failures() {
+        assert!(play_sound(&["lvx-no-such-player", "--id=bell"]).unwrap_err().starts_with("lvx-no-such-player: "));
+        assert!(play_sound(&[]).is_err());
+    }
 
-impl Log {
-    fn time_from_string(time_string: String) -> DateTime<Local> {
-        return match DateTime::parse_from_str(&time_string, "%Y-%m-%dT%H:%M:%S%.3f%z") {
-            Ok(ts) => {
-                ts.with_timezone(&Local)
-            }
+    #[test]
+    fn focus_step_follows_the_chain() {
+        let chain = ["message", "payload", "caller"].map(egui::Id::new);
+        assert_eq!(focus_step(&chain, chain[0], false), Some(chain[1]));
+        assert_eq!(focus_step(&chain, chain[2], true), Some(chain[1]));
+        // egui's own order takes over past either end and outside the fields.
+        assert_eq!(focus_step(&chain, chain[2], false), None);
+        assert_eq!(focus_step(&chain, chain[0], true), None);
+        assert_eq!(focus_step(&chain, egui::Id::new("table"), false), None);
+    }
+
+    #[test]
+    fn selection_note_counts_rows() {
+        let mut tab = open(&temp_log("selection_note.log", &json_lines(0..3)));
+        tab.selection.extend([0, 2]);
+        assert_eq!(tab.selection_note(2, true), "row 3 selected, 2 rows selected");
+        tab.selection.remove(&0);
+        assert_eq!(tab.selection_note(0, false), "row 1 deselected, 1 row selected");
+    }
 
-            _ => { Default::default() }
+    #[test]
+    fn profile_round_trip_and_errors() {
+        let path = std::path::PathBuf::from(temp_log("profile.json", ""));
+        let profile = Profile {
+            text_pattern: Some("(?P<message>.*)".to_string()),
+            theme: Some(Theme::Light),
+            time_precision: Some(TimePrecision::Micros),
+            ..Default::default()
         };
+        profile.write(&path).unwrap();
+        let read = Profile::read(&path).unwrap();
+        assert_eq!(read.text_pattern.as_deref(), Some("(?P<message>.*)"));
+        assert!(read.theme == Some(Theme::Light));
+        assert!(read.time_precision == Some(TimePrecision::Micros));
+        std::fs::write(&path, r#"{"utc_time": true}"#).unwrap();
+        let old = Profile::read(&path).unwrap();
+        assert!(old.utc_time && old.text_pattern.is_none() && old.theme.is_none());
+        std::fs::write(&path, "{").unwrap();
+        assert!(Profile::read(&path).is_err());
+        assert!(Profile::read(&path.with_file_name("missing.json")).is_err());
     }
-}
 
+    #[test]
+    fn append_into_a_sorted_view() {
+        let path = temp_log("append_sorted.log", &json_lines(0..6));
+        let mut tab = open(&path);
+        tab.sort = Some(Sort { column: SortColumn::Message, descending: true });
+        tab.apply_sort(tab.sort.unwrap());
+        tab.search_level_error = true;
+        tab.search();
+        while tab.search_step(std::time::Duration::from_secs(1)) {}
+        tab.search_next();
+        // m5, m4, m3, m2, m1, m0 with the cursor on m3 and m4 selected.
+        tab.selection = [1].into();
+        append(&path, &json_lines(6..9));
+        tab.follow_poll(None);
+        let messages: Vec<&str> = tab.filtered_logs.iter().map(|log| log.message.as_str()).collect();
+        assert_eq!(messages, ["m8", "m7", "m6", "m5", "m4", "m3", "m2", "m1", "m0"]);
+        assert_eq!(tab.search_founds, [1, 3, 5, 7]);
+        assert_eq!(tab.filtered_logs[tab.search_founds[tab.search_found_cursor]].message, "m3");
+        assert_eq!(tab.selection, [4].into());
+    }
 
-#[derive(Serialize, Deserialize)]
-struct JsonLine {
-    level: String,
-    ts: String,
-    msg: String,
-    #[serde(default)]
-    caller: String,
-    #[serde(flatten)]
-    payload: HashMap<String, serde_json::Value>,
-}
\ No newline at end of file
+    /// A busy stream appended into a sorted view, each poll is merged in one pass. The
+    /// bound leaves room for a debug build, `--nocapture` prints the time taken.
+    #[test]
+    fn follow_append_rate() {
+        let path = temp_log("append_rate.log", &json_lines(0..20_000));
+        let mut tab = open(&path);
+        tab.sort = Some(Sort { column: SortColumn::Message, descending: false });
+        tab.apply_sort(tab.sort.unwrap());
+        let (batches, batch) = (20, 1_000);
+        let mut merging = std::time::Duration::ZERO;
+        for round in 0..batches {
+            let from = 20_000 + round * batch;
+            append(&path, &json_lines(from..from + batch));
+            let started = std::time::Instant::now();
+            tab.follow_poll(None);
+            merging += started.elapsed();
+        }
+        println!("{} lines in {:?}", batches * batch, merging);
+        assert_eq!(tab.filtered_logs.len(), 20_000 + batches * batch);
+        assert!(tab.filtered_logs.windows(2).all(|pair| pair[0].message <= pair[1].message));
+        assert!(merging < std::time::Duration::from_secs(5), "took {:?}", merging);
+    }
+
+    #[test]
+    fn search_cursor_steps_through_matches() {
+        let mut tab = tab_with_founds(&[2, 5, 9]);
+        tab.search_last();
+        assert_eq!((tab.search_found_cursor, tab.scroll_to_row), (2, Some(9)));
+        tab.search_next();
+        assert_eq!((tab.search_found_cursor, tab.scroll_to_row), (2, None));
+        tab.search_previous();
+        assert_eq!((tab.search_found_cursor, tab.scroll_to_row), (1, Some(5)));
+        tab.search_first();
+        assert_eq!((tab.search_found_cursor, tab.scroll_to_row), (0, Some(2)));
+        tab.search_previous();
+        assert_eq!((tab.search_found_cursor, tab.scroll_to_row), (0, None));
+    }
+
+    #[test]
+    fn search_cursor_without_matches() {
+        let mut tab = tab_with_founds(&[]);
+        tab.search_first();
+        tab.search_last();
+        tab.search_next();
+        tab.search_previous();
+        assert_eq!((tab.search_found_cursor, tab.scroll_to_row), (0, None));
+    }
+
+    #[test]
+    fn search_cursor_follows_its_match_through_the_filter() {
+        let path = temp_log("search_filter.log", &json_lines(0..8));
+        let mut tab = open(&path);
+        (tab.search_level_info, tab.search_level_error) = (true, true);
+        tab.search_message = "m".to_string();
+        tab.search();
+        while tab.search_step(std::time::Duration::from_secs(1)) {}
+        tab.search_next();
+        tab.search_next();
+        tab.search_next();
+        assert_eq!(tab.filtered_logs[tab.search_founds[tab.search_found_cursor]].message, "m3");
+        // m3 is an ERROR row and survives hiding INFO, the cursor stays on it.
+        tab.filter_level_info = false;
+        tab.filter();
+        while tab.search_step(std::time::Duration::from_secs(1)) {}
+        assert_eq!(tab.search_founds, [0, 1, 2, 3]);
+        assert_eq!(tab.search_found_cursor, 1);
+        assert!(tab.index_at_search_found_cursor(1));
+        assert_eq!(tab.filtered_logs[1].message, "m3");
+        // m7 is hidden by the filter, the old index is clamped into the remaining matches.
+        tab.filter_level_info = true;
+        tab.filter();
+        while tab.search_step(std::time::Duration::from_secs(1)) {}
+        tab.search_last();
+        tab.filter_message = "m1".to_string();
+        tab.filter();
+        while tab.search_step(std::time::Duration::from_secs(1)) {}
+        assert_eq!(tab.search_founds, [0]);
+        assert_eq!(tab.search_found_cursor, 0);
+        assert!(tab.index_at_search_found_cursor(0));
+    }
+
+    #[test]
+    fn search_cursor_keeps_scroll() {
+        let mut tab = tab_with_founds(&[2, 5]);
+        tab.search_keep_scroll = true;
+        tab.search_next();
+        assert_eq!((tab.search_found_cursor, tab.scroll_to_row), (1, None));
+    }
+}